@@ -10,10 +10,16 @@ export interface FragmentInfo {
 
 export interface ToolResult {
   content?: Array<{ type: string; text?: string }>;
+  structuredContent?: unknown;
 }
 
 export interface RecommendResult {
-  persona: string;
+  persona: {
+    name: string;
+    description: string;
+    level?: string;
+    skill_groups: string[];
+  };
   skills: FragmentInfo[];
   contexts: FragmentInfo[];
   tones: FragmentInfo[];
@@ -29,39 +35,41 @@ export function extractToolText(result: unknown): string | undefined {
 }
 
 /**
- * Parse a recommend tool response into a RecommendResult.
+ * Extract the structured content from a tool result, falling back to parsing
+ * the text content as JSON for servers that predate output schemas.
  */
-export function parseRecommendResult(result: unknown): RecommendResult | undefined {
+export function extractStructured<T>(result: unknown): T | undefined {
+  const toolResult = result as ToolResult;
+  if (toolResult?.structuredContent !== undefined) {
+    return toolResult.structuredContent as T;
+  }
   const text = extractToolText(result);
   if (!text) {
     return undefined;
   }
   try {
-    return JSON.parse(text) as RecommendResult;
+    return JSON.parse(text) as T;
   } catch {
     return undefined;
   }
 }
 
+/**
+ * Parse a recommend tool response into a RecommendResult.
+ */
+export function parseRecommendResult(result: unknown): RecommendResult | undefined {
+  return extractStructured<RecommendResult>(result);
+}
+
 /**
  * Parse the list tool response into an array of FragmentInfo objects.
  */
 function parseFragments(result: unknown): FragmentInfo[] {
-  const toolResult = result as ToolResult;
-  if (!toolResult?.content?.length) {
-    return [];
-  }
-
-  const text = toolResult.content.find((c) => c.type === "text")?.text;
-  if (!text) {
-    return [];
-  }
-
-  try {
-    return JSON.parse(text) as FragmentInfo[];
-  } catch {
-    return [];
+  const parsed = extractStructured<{ fragments?: FragmentInfo[] } | FragmentInfo[]>(result);
+  if (Array.isArray(parsed)) {
+    return parsed;
   }
+  return parsed?.fragments ?? [];
 }
 
 /**
@@ -1,6 +1,6 @@
 import * as vscode from "vscode";
 import { MimicClient } from "./mimic-client";
-import { fetchFragments, extractToolText } from "./picker";
+import { fetchFragments, extractStructured, extractToolText } from "./picker";
 
 /**
  * Run the resolve command: optionally pick a persona and enter tags/groups,
@@ -59,20 +59,17 @@ export async function runResolve(client: MimicClient): Promise<void> {
   }
 
   const result = await client.callTool("resolve", args);
+  const structured = extractStructured<unknown>(result);
   const text = extractToolText(result);
 
-  if (!text) {
+  if (structured === undefined && !text) {
     vscode.window.showErrorMessage("Resolve returned no output.");
     return;
   }
 
   // Pretty-print and show in editor
-  let formatted: string;
-  try {
-    formatted = JSON.stringify(JSON.parse(text), null, 2);
-  } catch {
-    formatted = text;
-  }
+  const formatted =
+    structured !== undefined ? JSON.stringify(structured, null, 2) : (text ?? "");
 
   const doc = await vscode.workspace.openTextDocument({
     content: formatted,
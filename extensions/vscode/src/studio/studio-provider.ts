@@ -2,6 +2,7 @@ import * as vscode from "vscode";
 import { MimicClient } from "../mimic-client";
 import {
   fetchFragments,
+  extractStructured,
   extractToolText,
   parseRecommendResult,
   FragmentInfo,
@@ -119,13 +120,15 @@ export class StudioProvider {
       if (msg.groups.length) args.groups = msg.groups;
 
       const result = await this.client.callTool("resolve", args);
-      const text = extractToolText(result);
-      if (!text) {
+      const parsed = extractStructured<{
+        nodes?: Array<{ name: string; category: string; description?: string }>;
+        edges?: Array<{ from: string; to: string; relation?: string }>;
+      }>(result);
+      if (!parsed) {
         this.post({ type: "resolveResult", nodes: [], edges: [] });
         return;
       }
 
-      const parsed = JSON.parse(text);
       const nodes: ResolveNode[] = (parsed.nodes ?? []).map(
         (f: { name: string; category: string; description?: string }) => ({
           name: f.name,
@@ -0,0 +1,532 @@
+use crate::fragment::{parse_frontmatter_strict, Category, Frontmatter, Source};
+use crate::lint::{self, Linter, Severity, SourceMap};
+use crate::registry::Registry;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub struct FragmentDraft {
+    pub category: Category,
+    pub name: String,
+    pub frontmatter: Frontmatter,
+    pub body: String,
+}
+
+/// Fields to change on an existing fragment. `None` leaves a field untouched.
+#[derive(Debug, Default)]
+pub struct FragmentPatch {
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub group: Option<String>,
+    pub level: Option<String>,
+    pub skill_groups: Option<Vec<String>>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WriteResult {
+    pub name: String,
+    pub category: String,
+    pub source: Source,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Parses a writable layer name. Built-ins ship inside the binary and are
+/// never writable.
+pub fn parse_layer(s: &str) -> Result<Source, String> {
+    match s {
+        "project" => Ok(Source::Project),
+        "global" => Ok(Source::Global),
+        "builtin" => Err("Built-in fragments cannot be modified".to_string()),
+        _ => Err(format!(
+            "Unknown layer '{}' (expected 'project' or 'global')",
+            s
+        )),
+    }
+}
+
+pub fn create_fragment(
+    registry: &Registry,
+    layer: Source,
+    draft: &FragmentDraft,
+) -> Result<WriteResult, String> {
+    validate_name(&draft.name)?;
+    let base = writable_dir(registry, layer)?;
+    let path = base
+        .join(draft.category.dir_name())
+        .join(format!("{}.md", draft.name));
+    if path.exists() {
+        return Err(format!(
+            "{} '{}' already exists at {}; use update_fragment instead",
+            draft.category,
+            draft.name,
+            path.display()
+        ));
+    }
+
+    let content = draft.frontmatter.render(&draft.body);
+    let warnings = lint_before_write(
+        &content,
+        &path,
+        &draft.name,
+        draft.category.clone(),
+        layer,
+        false,
+        registry,
+    )?;
+    write_file(&path, &content)?;
+
+    Ok(WriteResult {
+        name: draft.name.clone(),
+        category: draft.category.to_string(),
+        source: layer,
+        path,
+        warnings,
+    })
+}
+
+pub fn update_fragment(
+    registry: &Registry,
+    category: Category,
+    name: &str,
+    patch: &FragmentPatch,
+) -> Result<WriteResult, String> {
+    let (path, source) = writable_fragment(registry, category.clone(), name)?;
+    let existing = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_frontmatter_strict(&existing)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let content = apply_patch(&existing, patch);
+    let is_root_level = path.parent() == registry.layer_dir(source);
+    let warnings = lint_before_write(
+        &content,
+        &path,
+        name,
+        category.clone(),
+        source,
+        is_root_level,
+        registry,
+    )?;
+    write_file(&path, &content)?;
+
+    Ok(WriteResult {
+        name: name.to_string(),
+        category: category.to_string(),
+        source,
+        path,
+        warnings,
+    })
+}
+
+pub fn delete_fragment(
+    registry: &Registry,
+    category: Category,
+    name: &str,
+) -> Result<WriteResult, String> {
+    let (path, source) = writable_fragment(registry, category.clone(), name)?;
+    std::fs::remove_file(&path)
+        .map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+    Ok(WriteResult {
+        name: name.to_string(),
+        category: category.to_string(),
+        source,
+        path,
+        warnings: vec![],
+    })
+}
+
+/// `existing` with the keys `patch` sets rewritten in place and its body
+/// replaced when the patch has one. Comments, unknown keys, and untouched
+/// keys are left as they were.
+fn apply_patch(existing: &str, patch: &FragmentPatch) -> String {
+    let mut content = existing.to_string();
+    let fields = [
+        (
+            "description",
+            patch.description.as_ref().map(|d| Frontmatter {
+                description: Some(d.clone()),
+                ..Frontmatter::default()
+            }),
+        ),
+        (
+            "tags",
+            patch.tags.as_ref().map(|t| Frontmatter {
+                tags: t.clone(),
+                ..Frontmatter::default()
+            }),
+        ),
+        (
+            "group",
+            patch.group.as_ref().map(|g| Frontmatter {
+                group: Some(g.clone()),
+                ..Frontmatter::default()
+            }),
+        ),
+        (
+            "level",
+            patch.level.as_ref().map(|l| Frontmatter {
+                level: Some(l.clone()),
+                ..Frontmatter::default()
+            }),
+        ),
+        (
+            "skill_groups",
+            patch.skill_groups.as_ref().map(|sg| Frontmatter {
+                skill_groups: sg.clone(),
+                ..Frontmatter::default()
+            }),
+        ),
+    ];
+    for (key, fm) in fields {
+        let Some(fm) = fm else {
+            continue;
+        };
+        // Render the one field the way new fragments are written; an empty
+        // list renders nothing and so removes the key
+        let rendered: Vec<String> = fm
+            .render("")
+            .lines()
+            .skip(1)
+            .take_while(|l| *l != "---")
+            .map(str::to_string)
+            .collect();
+        content = set_key(&content, key, rendered);
+    }
+    match &patch.body {
+        Some(body) if SourceMap::new(&content).frontmatter.is_none() => {
+            format!("{}\n", body.trim())
+        }
+        Some(body) => {
+            let mut lines = file_lines(&content);
+            let close = frontmatter_close(&mut lines);
+            lines.truncate(close + 1);
+            lines.push(body.trim().to_string());
+            lines.join("\n") + "\n"
+        }
+        None => content,
+    }
+}
+
+/// Replaces the lines of top-level `key` with `rendered`, or adds them at
+/// the end of the frontmatter when the key is missing.
+fn set_key(content: &str, key: &str, rendered: Vec<String>) -> String {
+    let mut lines = file_lines(content);
+    let range = match lint::key_lines(content, &SourceMap::new(content), key) {
+        Some(range) => range,
+        None => {
+            let close = frontmatter_close(&mut lines);
+            close..close
+        }
+    };
+    lines.splice(range, rendered);
+    let mut out = lines.join("\n");
+    if content.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Index of the closing `---` of the frontmatter in `lines`, adding an empty
+/// frontmatter at the top when there is none.
+fn frontmatter_close(lines: &mut Vec<String>) -> usize {
+    let content = lines.join("\n");
+    match SourceMap::new(&content).frontmatter {
+        Some(open) => (open.line..lines.len())
+            .find(|&i| lines[i].starts_with("---"))
+            .unwrap_or(lines.len()),
+        None => {
+            lines.splice(0..0, ["---".to_string(), "---".to_string()]);
+            1
+        }
+    }
+}
+
+fn file_lines(content: &str) -> Vec<String> {
+    content.lines().map(str::to_string).collect()
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        // A dot would make the file a variant or translation of another fragment
+        Err(format!(
+            "Invalid fragment name '{}': use lowercase letters, digits, '-' or '_'",
+            name
+        ))
+    }
+}
+
+fn writable_dir(registry: &Registry, layer: Source) -> Result<&Path, String> {
+    match layer {
+        Source::BuiltIn => Err("Built-in fragments cannot be modified".to_string()),
+        Source::Project => registry
+            .layer_dir(layer)
+            .ok_or_else(|| "No project directory (.mimic/) found; use layer 'global'".to_string()),
+        Source::Global => registry
+            .layer_dir(layer)
+            .ok_or_else(|| "No global directory (~/.mimic/) available".to_string()),
+    }
+}
+
+/// Locates the file behind the effective fragment, refusing built-ins.
+fn writable_fragment(
+    registry: &Registry,
+    category: Category,
+    name: &str,
+) -> Result<(PathBuf, Source), String> {
+    let frag = registry
+        .get(category.clone(), name)
+        .ok_or_else(|| format!("{} '{}' not found", category, name))?;
+    match (&frag.path, frag.source) {
+        (Some(path), Source::Project | Source::Global) => Ok((path.clone(), frag.source)),
+        _ => Err(format!(
+            "{} '{}' is built-in and cannot be modified; create a project or global override instead",
+            category, name
+        )),
+    }
+}
+
+/// Runs the lint rules, as configured by the registry's `lint.rules`, over
+/// the content about to be written. Errors abort the write; warnings are
+/// returned so the caller can surface them.
+fn lint_before_write(
+    content: &str,
+    path: &Path,
+    name: &str,
+    category: Category,
+    source: Source,
+    is_root_level: bool,
+    registry: &Registry,
+) -> Result<Vec<String>, String> {
+    let mut linter = Linter::new();
+    linter
+        .configure(&registry.config().lint.rules, &[])
+        .map_err(|e| format!("config.yaml: {e}"))?;
+    let display_path = path.display().to_string();
+    let ctx = lint::build_context(
+        content,
+        &display_path,
+        name,
+        category,
+        source,
+        is_root_level,
+        registry.categories(),
+    );
+    let diagnostics = linter.check(&ctx);
+
+    let errors: Vec<String> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| format!("[{}] {}", d.rule, d.message))
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!(
+            "Refusing to write {}: {}",
+            display_path,
+            errors.join("; ")
+        ));
+    }
+
+    Ok(diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(|d| format!("[{}] {}", d.rule, d.message))
+        .collect())
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(name: &str, body: &str) -> FragmentDraft {
+        FragmentDraft {
+            category: Category::Skill,
+            name: name.to_string(),
+            frontmatter: Frontmatter {
+                description: Some("Session-distilled skill".to_string()),
+                tags: vec!["test".to_string()],
+                group: Some("backend".to_string()),
+                ..Frontmatter::default()
+            },
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn create_writes_project_fragment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let result = create_fragment(
+            &registry,
+            Source::Project,
+            &draft("distilled", "You know things."),
+        )
+        .unwrap();
+        assert_eq!(result.path, tmp.path().join("skills/distilled.md"));
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        let reloaded = Registry::new(Some(tmp.path().to_path_buf()));
+        let frag = reloaded.get(Category::Skill, "distilled").unwrap();
+        assert_eq!(frag.body, "You know things.");
+        assert_eq!(frag.group.as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn create_refuses_existing_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        create_fragment(&registry, Source::Project, &draft("dup", "Body.")).unwrap();
+        let err = create_fragment(&registry, Source::Project, &draft("dup", "Body.")).unwrap_err();
+        assert!(err.contains("already exists"));
+    }
+
+    #[test]
+    fn create_refuses_lint_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let err = create_fragment(&registry, Source::Project, &draft("empty", "  ")).unwrap_err();
+        assert!(err.contains("non-empty-body"), "{err}");
+        assert!(!tmp.path().join("skills/empty.md").exists());
+    }
+
+    #[test]
+    fn create_follows_configured_rule_levels() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.yaml"),
+            "lint:\n  rules:\n    non-empty-body: off\n    no-todo: error\n",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        create_fragment(&registry, Source::Project, &draft("empty", "  ")).unwrap();
+        let err = create_fragment(&registry, Source::Project, &draft("draft", "TODO: finish."))
+            .unwrap_err();
+        assert!(err.contains("no-todo"), "{err}");
+    }
+
+    #[test]
+    fn create_rejects_path_like_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        for name in ["../escape", "a/b", "", ".hidden", "Upper", "go.small"] {
+            assert!(
+                create_fragment(&registry, Source::Project, &draft(name, "Body.")).is_err(),
+                "name {name:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn create_can_override_builtin_in_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        create_fragment(&registry, Source::Project, &draft("go", "Project Go.")).unwrap();
+        let reloaded = Registry::new(Some(tmp.path().to_path_buf()));
+        assert_eq!(
+            reloaded.get(Category::Skill, "go").unwrap().source,
+            Source::Project
+        );
+    }
+
+    #[test]
+    fn update_patches_fields_and_keeps_the_rest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        create_fragment(&registry, Source::Project, &draft("patched", "Old body.")).unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let patch = FragmentPatch {
+            body: Some("New body.".to_string()),
+            tags: Some(vec!["updated".to_string()]),
+            ..FragmentPatch::default()
+        };
+        update_fragment(&registry, Category::Skill, "patched", &patch).unwrap();
+
+        let reloaded = Registry::new(Some(tmp.path().to_path_buf()));
+        let frag = reloaded.get(Category::Skill, "patched").unwrap();
+        assert_eq!(frag.body, "New body.");
+        assert_eq!(frag.tags, vec!["updated"]);
+        assert_eq!(frag.description, "Session-distilled skill");
+        assert_eq!(frag.group.as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn update_keeps_comments_and_unknown_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("skills")).unwrap();
+        let path = tmp.path().join("skills/commented.md");
+        std::fs::write(
+            &path,
+            "---\n# mimic-lint-disable html-comment\ndescription: Old\ntags:\n  - a # first\n  - b\nowner: payments-team\n---\nBody <!-- kept -->\n",
+        )
+        .unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let patch = FragmentPatch {
+            description: Some("New: with a colon".to_string()),
+            group: Some("backend".to_string()),
+            ..FragmentPatch::default()
+        };
+        update_fragment(&registry, Category::Skill, "commented", &patch).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "---\n# mimic-lint-disable html-comment\ndescription: \"New: with a colon\"\ntags:\n  - a # first\n  - b\nowner: payments-team\ngroup: backend\n---\nBody <!-- kept -->\n"
+        );
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let patch = FragmentPatch {
+            tags: Some(vec![]),
+            body: Some("New body.".to_string()),
+            ..FragmentPatch::default()
+        };
+        update_fragment(&registry, Category::Skill, "commented", &patch).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "---\n# mimic-lint-disable html-comment\ndescription: \"New: with a colon\"\nowner: payments-team\ngroup: backend\n---\nNew body.\n"
+        );
+    }
+
+    #[test]
+    fn update_and_delete_refuse_builtins() {
+        let registry = Registry::new(None);
+        let err = update_fragment(&registry, Category::Skill, "go", &FragmentPatch::default())
+            .unwrap_err();
+        assert!(err.contains("built-in"));
+        let err = delete_fragment(&registry, Category::Skill, "go").unwrap_err();
+        assert!(err.contains("built-in"));
+    }
+
+    #[test]
+    fn delete_removes_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        create_fragment(&registry, Source::Project, &draft("doomed", "Body.")).unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let result = delete_fragment(&registry, Category::Skill, "doomed").unwrap();
+        assert!(!result.path.exists());
+    }
+
+    #[test]
+    fn parse_layer_rejects_builtin() {
+        assert_eq!(parse_layer("project"), Ok(Source::Project));
+        assert_eq!(parse_layer("global"), Ok(Source::Global));
+        assert!(parse_layer("builtin").is_err());
+        assert!(parse_layer("other").is_err());
+    }
+}
@@ -1,10 +1,16 @@
+use crate::lint;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
-#[command(name = "mimic", version, about)]
+#[command(name = "mimic", version, about, args_conflicts_with_subcommands = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Run the server refusing to compose when a fragment or the layout
+    /// differs from .mimic/mimic.lock
+    #[arg(long)]
+    pub locked: bool,
 }
 
 #[derive(Subcommand)]
@@ -14,5 +20,42 @@ pub enum Commands {
         /// Show warnings in addition to errors
         #[arg(short, long)]
         warnings: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: lint::Format,
+        /// Apply automatic fixes to project and global fragments
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, print the fixes as a diff instead of writing them
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+        /// Run only this rule; repeat to run several
+        #[arg(long = "rule", value_name = "RULE")]
+        rules: Vec<String>,
+        /// Skip diagnostics for built-in fragments
+        #[arg(long)]
+        no_builtin: bool,
+        /// Keep running and re-lint fragment files as they change
+        #[arg(long)]
+        watch: bool,
+        /// Fragment files or directories to lint instead of every layer
+        paths: Vec<std::path::PathBuf>,
+    },
+    /// Compose two presets or compose specs and show what changed between them
+    Diff {
+        /// Left-hand side: a preset name, persona name, or spec like "backend-engineer?skills=go"
+        left: String,
+        /// Right-hand side, in the same forms as LEFT
+        right: String,
+    },
+    /// Record content hashes of the fragments used by presets and compositions in .mimic/mimic.lock
+    Lock {
+        /// Compositions to pin besides the presets: preset names, persona names, or specs like "backend-engineer?skills=go"
+        specs: Vec<String>,
+        /// Check that the lockfile is up to date instead of writing it
+        #[arg(long)]
+        check: bool,
     },
+    /// Run a language server over stdio for editing fragment files
+    Lsp,
 }
@@ -1,86 +1,426 @@
-use crate::fragment::Category;
+use crate::config::{self, Layout};
+use crate::example::{self, Turn};
+use crate::fragment::{Category, Fragment, Source};
 use crate::registry::Registry;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
 
+#[derive(Debug, Clone)]
 pub struct ComposeRequest {
     pub persona: String,
     pub skills: Vec<String>,
     pub contexts: Vec<String>,
     pub tones: Vec<String>,
     pub constraints: Vec<String>,
+    pub examples: Vec<String>,
+    /// Model target used to pick fragment variants (e.g. "small", "claude").
+    pub target: Option<String>,
+    /// Language to compose in (e.g. "ja", "pt-BR"); fragments without a
+    /// translation keep their default body.
+    pub locale: Option<String>,
+    /// Use each deprecated fragment's `replaced_by` fragment in its place.
+    pub replace_deprecated: bool,
+    /// Fragments for custom categories, keyed by category name.
+    pub custom: BTreeMap<String, Vec<String>>,
+}
+
+/// The composed prompt together with the provenance of each fragment span.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Composition {
+    pub prompt: String,
+    pub spans: Vec<Span>,
+    /// Problems worth surfacing that didn't stop composition, such as
+    /// deprecated fragments.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// A byte range of the composed prompt and the fragment that produced it.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Span {
+    /// Byte offset where the fragment body starts in the prompt.
+    pub start: usize,
+    /// Byte offset just past the end of the fragment body.
+    pub end: usize,
+    pub category: String,
+    pub name: String,
+    pub source: Source,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Variant used instead of the default body, when the target picked one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    /// Language of the text, when the fragment declares one or was translated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    pub reason: Reason,
+}
+
+/// A composition split into a system prompt and the example exchanges as
+/// prior conversation turns, for chat APIs that take a message list.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Conversation {
+    pub system: String,
+    pub messages: Vec<Turn>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Why a fragment ended up in the prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    /// Named explicitly in the compose request (or the preset it came from).
+    Requested,
+    /// Substituted through `replaced_by` for the deprecated fragment `from`,
+    /// which the request named.
+    Replaced { from: String },
+    /// Added as the layout's preamble or epilogue.
+    Configured,
 }
 
 pub fn compose(registry: &Registry, req: &ComposeRequest) -> Result<String, String> {
-    let persona = registry
-        .get(Category::Persona, &req.persona)
-        .ok_or_else(|| format!("Persona '{}' not found", req.persona))?;
-
-    let mut output = persona.body.clone();
-
-    // Skills
-    if !req.skills.is_empty() {
-        output.push_str("\n\n## Expertise\n\n");
-        let mut first = true;
-        for skill_name in &req.skills {
-            let skill = registry
-                .get(Category::Skill, skill_name)
-                .ok_or_else(|| format!("Skill '{}' not found", skill_name))?;
-            if !first {
-                output.push_str("\n\n");
+    compose_explained(registry, req).map(|c| c.prompt)
+}
+
+/// Composes the prompt using the layout from the registry's config and
+/// records which fragment produced each span of it.
+pub fn compose_explained(registry: &Registry, req: &ComposeRequest) -> Result<Composition, String> {
+    compose_with_layout(registry, req, &registry.config().layout)
+}
+
+pub fn compose_with_layout(
+    registry: &Registry,
+    req: &ComposeRequest,
+    layout: &Layout,
+) -> Result<Composition, String> {
+    let mut warnings = Vec::new();
+    let mut pick = |category: Category, name: &str| {
+        pick_fragment(
+            registry,
+            category,
+            name,
+            req.replace_deprecated,
+            &mut warnings,
+        )
+    };
+    let persona = pick(Category::Persona, &req.persona)?;
+
+    let requested = [
+        (Category::Skill, &req.skills),
+        (Category::Context, &req.contexts),
+        (Category::Tone, &req.tones),
+        (Category::Constraint, &req.constraints),
+        (Category::Example, &req.examples),
+    ];
+    let mut selected: Vec<(Category, Vec<Picked>)> = vec![(Category::Persona, vec![persona])];
+    for (category, names) in requested {
+        let frags = names
+            .iter()
+            .map(|name| pick(category.clone(), name))
+            .collect::<Result<Vec<_>, String>>()?;
+        selected.push((category, frags));
+    }
+    for (key, names) in &req.custom {
+        let category = registry
+            .category(key)
+            .ok_or_else(|| format!("Unknown category '{}'", key))?;
+        for name in names {
+            let frag = pick(category.clone(), name)?;
+            match selected.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, frags)) => frags.push(frag),
+                None => selected.push((category.clone(), vec![frag])),
             }
-            output.push_str(&skill.body);
-            first = false;
         }
     }
+    let preamble = layout_fragment(registry, layout.preamble.as_deref(), "Preamble")?;
+    let epilogue = layout_fragment(registry, layout.epilogue.as_deref(), "Epilogue")?;
 
-    // Contexts
-    if !req.contexts.is_empty() {
-        output.push_str("\n\n## Context\n\n");
-        let mut first = true;
-        for ctx_name in &req.contexts {
-            let ctx = registry
-                .get(Category::Context, ctx_name)
-                .ok_or_else(|| format!("Context '{}' not found", ctx_name))?;
-            if !first {
+    let target = req.target.as_deref();
+    let locale = req.locale.as_deref();
+    let mut output = String::new();
+    let mut spans = Vec::new();
+    if let Some(frag) = preamble {
+        push_fragment(
+            &mut output,
+            &mut spans,
+            frag,
+            target,
+            locale,
+            Reason::Configured,
+        );
+    }
+    for category in layout.resolved_order(registry.categories())? {
+        let frags = selected
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, f)| f.as_slice())
+            .unwrap_or_default();
+        if frags.is_empty() {
+            continue;
+        }
+        if !output.is_empty() {
+            output.push_str("\n\n");
+        }
+        if let Some(heading) = layout.heading(&category, registry.categories())? {
+            output.push_str(&format!("## {}\n\n", heading));
+        }
+        for (i, picked) in frags.iter().enumerate() {
+            if i > 0 {
                 output.push_str("\n\n");
             }
-            output.push_str(&ctx.body);
-            first = false;
+            let reason = match &picked.1 {
+                Some(from) => Reason::Replaced { from: from.clone() },
+                None => Reason::Requested,
+            };
+            push_fragment(&mut output, &mut spans, picked.0, target, locale, reason);
         }
     }
+    if let Some(frag) = epilogue {
+        if !output.is_empty() {
+            output.push_str("\n\n");
+        }
+        push_fragment(
+            &mut output,
+            &mut spans,
+            frag,
+            target,
+            locale,
+            Reason::Configured,
+        );
+    }
 
-    // Tones
-    if !req.tones.is_empty() {
-        output.push_str("\n\n## Communication Style\n\n");
-        let mut first = true;
-        for tone_name in &req.tones {
-            let tone = registry
-                .get(Category::Tone, tone_name)
-                .ok_or_else(|| format!("Tone '{}' not found", tone_name))?;
-            if !first {
-                output.push_str("\n\n");
-            }
-            output.push_str(&tone.body);
-            first = false;
+    Ok(Composition {
+        prompt: output,
+        spans,
+        warnings,
+    })
+}
+
+/// Composes the system prompt without the requested examples and returns
+/// their exchanges as messages instead.
+pub fn compose_conversation(
+    registry: &Registry,
+    req: &ComposeRequest,
+    layout: &Layout,
+) -> Result<Conversation, String> {
+    let mut messages = Vec::new();
+    let mut warnings = Vec::new();
+    for name in &req.examples {
+        let (frag, _) = pick_fragment(
+            registry,
+            Category::Example,
+            name,
+            req.replace_deprecated,
+            &mut warnings,
+        )?;
+        let (body, _) = frag
+            .localized(req.locale.as_deref())
+            .body_for(req.target.as_deref());
+        let turns = example::parse_turns(body)
+            .map_err(|e| format!("Example '{}' is not a valid exchange: {}", name, e))?;
+        messages.extend(turns);
+    }
+    let system_req = ComposeRequest {
+        examples: vec![],
+        ..req.clone()
+    };
+    let composition = compose_with_layout(registry, &system_req, layout)?;
+    warnings.extend(composition.warnings);
+    Ok(Conversation {
+        system: composition.prompt,
+        messages,
+        warnings,
+    })
+}
+
+/// A fragment chosen for the prompt, with the deprecated fragment it stands
+/// in for when it was substituted.
+type Picked<'a> = (&'a Fragment, Option<String>);
+
+/// Looks up a requested fragment, warning when it is deprecated and
+/// following `replaced_by` links when `replace` is set.
+fn pick_fragment<'a>(
+    registry: &'a Registry,
+    category: Category,
+    name: &str,
+    replace: bool,
+    warnings: &mut Vec<String>,
+) -> Result<Picked<'a>, String> {
+    let frag = registry
+        .get(category.clone(), name)
+        .ok_or_else(|| format!("{} '{}' not found", label(&category), name))?;
+    if !frag.deprecated {
+        return Ok((frag, None));
+    }
+    let mut current = frag;
+    if replace {
+        let mut seen = vec![frag.name.as_str()];
+        while let Some(next) = current
+            .replaced_by
+            .as_deref()
+            .filter(|_| current.deprecated)
+            .filter(|n| !seen.contains(n))
+            .and_then(|n| registry.get(category.clone(), n))
+        {
+            seen.push(&next.name);
+            current = next;
         }
     }
+    if current.name != frag.name {
+        warnings.push(format!(
+            "{} '{}' is deprecated; using '{}' instead",
+            label(&category),
+            name,
+            current.name
+        ));
+        return Ok((current, Some(frag.name.clone())));
+    }
+    let mut warning = format!("{} '{}' is deprecated", label(&category), name);
+    if let Some(ref since) = frag.since {
+        warning.push_str(&format!(" since {}", since));
+    }
+    if let Some(ref replacement) = frag.replaced_by {
+        warning.push_str(&format!("; use '{}' instead", replacement));
+    }
+    warnings.push(warning);
+    Ok((frag, None))
+}
 
-    // Constraints
-    if !req.constraints.is_empty() {
-        output.push_str("\n\n## Constraints\n\n");
-        let mut first = true;
-        for constraint_name in &req.constraints {
-            let constraint = registry
-                .get(Category::Constraint, constraint_name)
-                .ok_or_else(|| format!("Constraint '{}' not found", constraint_name))?;
-            if !first {
-                output.push_str("\n\n");
+fn label(category: &Category) -> &str {
+    match category {
+        Category::Persona => "Persona",
+        Category::Skill => "Skill",
+        Category::Context => "Context",
+        Category::Tone => "Tone",
+        Category::Constraint => "Constraint",
+        Category::Example => "Example",
+        Category::Custom(def) => &def.name,
+    }
+}
+
+/// Looks up a layout preamble or epilogue given as "category/name".
+fn layout_fragment<'a>(
+    registry: &'a Registry,
+    reference: Option<&str>,
+    what: &str,
+) -> Result<Option<&'a Fragment>, String> {
+    let Some(reference) = reference else {
+        return Ok(None);
+    };
+    let (category, name) = config::parse_fragment_ref(reference, registry.categories())?;
+    registry
+        .get(category, name)
+        .map(Some)
+        .ok_or_else(|| format!("{} fragment '{}' not found", what, reference))
+}
+
+fn push_fragment(
+    output: &mut String,
+    spans: &mut Vec<Span>,
+    frag: &Fragment,
+    target: Option<&str>,
+    locale: Option<&str>,
+    reason: Reason,
+) {
+    let localized = frag.localized(locale);
+    let (body, variant) = localized.body_for(target);
+    let start = output.len();
+    if frag.category == Category::Example {
+        output.push_str(&example::render_inline(body));
+    } else {
+        output.push_str(body);
+    }
+    spans.push(Span {
+        start,
+        end: output.len(),
+        category: frag.category.to_string(),
+        name: frag.name.clone(),
+        source: frag.source,
+        path: localized.path.as_ref().map(|p| p.display().to_string()),
+        variant: variant.map(str::to_string),
+        locale: localized.locale.clone(),
+        reason,
+    });
+}
+
+/// Parses a compose spec of the form `persona?skills=a,b&tones=c&project=p`,
+/// the same syntax as the `mimic://compose/` resource template.
+pub fn parse_spec(spec: &str) -> Option<(ComposeRequest, Option<String>)> {
+    let (persona, query) = spec.split_once('?').unwrap_or((spec, ""));
+    let persona = percent_decode(persona);
+    if persona.is_empty() {
+        return None;
+    }
+    let mut req = ComposeRequest {
+        persona,
+        skills: vec![],
+        contexts: vec![],
+        tones: vec![],
+        constraints: vec![],
+        examples: vec![],
+        target: None,
+        locale: None,
+        replace_deprecated: false,
+        custom: Default::default(),
+    };
+    let mut project = None;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let values: Vec<String> = value
+            .split(',')
+            .map(percent_decode)
+            .filter(|v| !v.is_empty())
+            .collect();
+        match key {
+            "skills" => req.skills.extend(values),
+            "contexts" => req.contexts.extend(values),
+            "tones" => req.tones.extend(values),
+            "constraints" => req.constraints.extend(values),
+            "examples" => req.examples.extend(values),
+            "target" => req.target = values.into_iter().next(),
+            "locale" => req.locale = values.into_iter().next(),
+            "replace_deprecated" => {
+                req.replace_deprecated = values.iter().any(|v| v == "true" || v == "1")
             }
-            output.push_str(&constraint.body);
-            first = false;
+            "project" => project = values.into_iter().next(),
+            // Anything else names a custom category; compose validates it
+            _ => req
+                .custom
+                .entry(percent_decode(key))
+                .or_default()
+                .extend(values),
         }
     }
+    Some((req, project))
+}
 
-    Ok(output)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
 }
 
 #[cfg(test)]
@@ -96,6 +436,11 @@ mod tests {
             contexts: vec![],
             tones: vec![],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req).unwrap();
         assert!(!result.is_empty());
@@ -111,6 +456,11 @@ mod tests {
             contexts: vec!["code-review".to_string()],
             tones: vec!["concise".to_string()],
             constraints: vec!["no-frameworks".to_string()],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req).unwrap();
         assert!(result.contains("## Expertise"));
@@ -119,6 +469,179 @@ mod tests {
         assert!(result.contains("## Constraints"));
     }
 
+    #[test]
+    fn compose_conversation_moves_examples_to_messages() {
+        let registry = Registry::new(None);
+        let req = ComposeRequest {
+            persona: "backend-engineer".to_string(),
+            skills: vec!["go".to_string()],
+            contexts: vec![],
+            tones: vec![],
+            constraints: vec![],
+            examples: vec!["clarifying-question".to_string(), "code-review".to_string()],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        };
+        let inline = compose(&registry, &req).unwrap();
+        assert!(inline.contains("## Examples\n\n**User:** Make the import faster."));
+
+        let conversation =
+            compose_conversation(&registry, &req, &registry.config().layout).unwrap();
+        assert!(!conversation.system.contains("## Examples"));
+        assert!(conversation.system.contains("## Expertise"));
+        assert_eq!(conversation.messages.len(), 4);
+        assert_eq!(conversation.messages[0].content, "Make the import faster.");
+    }
+
+    #[test]
+    fn compose_target_picks_variants() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills = tmp.path().join("skills");
+        std::fs::create_dir_all(&skills).unwrap();
+        std::fs::write(
+            skills.join("house.md"),
+            "---\nvariants:\n  claude: Claude house rules.\n---\nHouse rules.",
+        )
+        .unwrap();
+        std::fs::write(skills.join("house.small.md"), "Short house rules.").unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let mut req = ComposeRequest {
+            persona: "backend-engineer".to_string(),
+            skills: vec!["house".to_string()],
+            contexts: vec![],
+            tones: vec![],
+            constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        };
+        assert!(compose(&registry, &req)
+            .unwrap()
+            .ends_with("\nHouse rules."));
+
+        req.target = Some("small".to_string());
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert!(composition.prompt.ends_with("\nShort house rules."));
+        assert_eq!(composition.spans[1].variant.as_deref(), Some("small"));
+        assert_eq!(composition.spans[0].variant, None);
+
+        req.target = Some("claude-opus".to_string());
+        assert!(compose(&registry, &req)
+            .unwrap()
+            .ends_with("\nClaude house rules."));
+    }
+
+    #[test]
+    fn compose_locale_uses_translations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let personas = tmp.path().join("personas");
+        std::fs::create_dir_all(&personas).unwrap();
+        std::fs::write(
+            personas.join("backend-engineer.ja.md"),
+            "あなたはシニアバックエンドエンジニアです。日本語で回答してください。",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let (req, _) = parse_spec("backend-engineer?skills=go&locale=ja-JP").unwrap();
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert!(composition.prompt.starts_with("あなたはシニア"));
+        assert_eq!(composition.spans[0].locale.as_deref(), Some("ja"));
+        // Untranslated fragments keep their default body
+        assert_eq!(composition.spans[1].locale, None);
+        assert!(composition.prompt.contains("## Expertise"));
+    }
+
+    #[test]
+    fn compose_warns_about_and_replaces_deprecated_fragments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills = tmp.path().join("skills");
+        std::fs::create_dir_all(&skills).unwrap();
+        std::fs::write(
+            skills.join("legacy-go.md"),
+            "---\ndeprecated: true\nreplaced_by: go\nsince: 2.0\n---\nOld Go advice.",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+
+        let (mut req, _) = parse_spec("backend-engineer?skills=legacy-go").unwrap();
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert!(composition.prompt.contains("Old Go advice."));
+        assert_eq!(
+            composition.warnings,
+            ["Skill 'legacy-go' is deprecated since 2.0; use 'go' instead"]
+        );
+
+        req.replace_deprecated = true;
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert!(!composition.prompt.contains("Old Go advice."));
+        assert_eq!(composition.spans[1].name, "go");
+        assert_eq!(
+            composition.spans[1].reason,
+            Reason::Replaced {
+                from: "legacy-go".to_string()
+            }
+        );
+        assert_eq!(
+            composition.warnings,
+            ["Skill 'legacy-go' is deprecated; using 'go' instead"]
+        );
+    }
+
+    #[test]
+    fn compose_conversation_warns_about_and_replaces_deprecated_examples() {
+        let tmp = tempfile::tempdir().unwrap();
+        let examples = tmp.path().join("examples");
+        std::fs::create_dir_all(&examples).unwrap();
+        std::fs::write(
+            examples.join("old-review.md"),
+            "---\ndeprecated: true\nreplaced_by: code-review\n---\n```user\nOld question.\n```\n```assistant\nOld answer.\n```\n",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let layout = &registry.config().layout;
+
+        let (mut req, _) = parse_spec("backend-engineer?examples=old-review").unwrap();
+        let conversation = compose_conversation(&registry, &req, layout).unwrap();
+        assert_eq!(conversation.messages[0].content, "Old question.");
+        assert_eq!(
+            conversation.warnings,
+            ["Example 'old-review' is deprecated; use 'code-review' instead"]
+        );
+
+        req.replace_deprecated = true;
+        let conversation = compose_conversation(&registry, &req, layout).unwrap();
+        assert_ne!(conversation.messages[0].content, "Old question.");
+        assert_eq!(
+            conversation.warnings,
+            ["Example 'old-review' is deprecated; using 'code-review' instead"]
+        );
+    }
+
+    #[test]
+    fn replacement_cycles_stop() {
+        let tmp = tempfile::tempdir().unwrap();
+        let tones = tmp.path().join("tones");
+        std::fs::create_dir_all(&tones).unwrap();
+        std::fs::write(
+            tones.join("a.md"),
+            "---\ndeprecated: true\nreplaced_by: b\n---\nA.",
+        )
+        .unwrap();
+        std::fs::write(
+            tones.join("b.md"),
+            "---\ndeprecated: true\nreplaced_by: a\n---\nB.",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let (req, _) = parse_spec("backend-engineer?tones=a&replace_deprecated=true").unwrap();
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert!(composition.prompt.ends_with("B."));
+    }
+
     #[test]
     fn compose_missing_persona() {
         let registry = Registry::new(None);
@@ -128,6 +651,11 @@ mod tests {
             contexts: vec![],
             tones: vec![],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req);
         assert!(result.is_err());
@@ -143,6 +671,11 @@ mod tests {
             contexts: vec![],
             tones: vec![],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req);
         assert!(result.is_err());
@@ -157,6 +690,11 @@ mod tests {
             contexts: vec!["nonexistent-context".to_string()],
             tones: vec![],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req);
         assert!(result.is_err());
@@ -172,6 +710,11 @@ mod tests {
             contexts: vec![],
             tones: vec!["nonexistent-tone".to_string()],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req);
         assert!(result.is_err());
@@ -187,6 +730,11 @@ mod tests {
             contexts: vec!["code-review".to_string(), "greenfield-project".to_string()],
             tones: vec![],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req).unwrap();
         assert!(result.contains("## Context"));
@@ -205,6 +753,11 @@ mod tests {
             contexts: vec![],
             tones: vec!["concise".to_string(), "pedagogical".to_string()],
             constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req).unwrap();
         assert!(result.contains("## Communication Style"));
@@ -221,9 +774,230 @@ mod tests {
             contexts: vec![],
             tones: vec![],
             constraints: vec!["nonexistent-constraint".to_string()],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
         };
         let result = compose(&registry, &req);
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Constraint"));
     }
+
+    #[test]
+    fn parse_spec_without_scheme() {
+        let (req, project) = parse_spec("designer?skills=css&constraints=no-frameworks").unwrap();
+        assert_eq!(req.persona, "designer");
+        assert_eq!(req.skills, vec!["css"]);
+        assert_eq!(req.constraints, vec!["no-frameworks"]);
+        assert!(project.is_none());
+        assert!(parse_spec("").is_none());
+    }
+
+    #[test]
+    fn percent_decode_handles_escapes() {
+        assert_eq!(percent_decode("a%2Fb+c"), "a/b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn explain_spans_cover_each_fragment_body() {
+        let registry = Registry::new(None);
+        let req = ComposeRequest {
+            persona: "backend-engineer".to_string(),
+            skills: vec!["go".to_string(), "postgresql".to_string()],
+            contexts: vec!["code-review".to_string()],
+            tones: vec!["concise".to_string()],
+            constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        };
+        let composition = compose_explained(&registry, &req).unwrap();
+        assert_eq!(composition.prompt, compose(&registry, &req).unwrap());
+
+        let names: Vec<&str> = composition.spans.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "backend-engineer",
+                "go",
+                "postgresql",
+                "code-review",
+                "concise"
+            ]
+        );
+        for span in &composition.spans {
+            let category = Category::from_name(&span.category).unwrap();
+            let frag = registry.get(category, &span.name).unwrap();
+            assert_eq!(&composition.prompt[span.start..span.end], frag.body);
+            assert_eq!(span.source, Source::BuiltIn);
+            assert!(span.path.is_none());
+            assert_eq!(span.reason, Reason::Requested);
+        }
+    }
+
+    #[test]
+    fn explain_reports_project_file_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills = tmp.path().join("skills");
+        std::fs::create_dir_all(&skills).unwrap();
+        std::fs::write(skills.join("go.md"), "Project Go guidance.").unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let req = ComposeRequest {
+            persona: "backend-engineer".to_string(),
+            skills: vec!["go".to_string()],
+            contexts: vec![],
+            tones: vec![],
+            constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        };
+        let composition = compose_explained(&registry, &req).unwrap();
+        let span = &composition.spans[1];
+        assert_eq!(span.source, Source::Project);
+        assert_eq!(
+            span.path.as_deref(),
+            Some(skills.join("go.md").display().to_string().as_str())
+        );
+        assert_eq!(
+            &composition.prompt[span.start..span.end],
+            "Project Go guidance."
+        );
+    }
+
+    fn layout(yaml: &str) -> Layout {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn go_review() -> ComposeRequest {
+        ComposeRequest {
+            persona: "backend-engineer".to_string(),
+            skills: vec!["go".to_string()],
+            contexts: vec![],
+            tones: vec!["concise".to_string()],
+            constraints: vec!["no-frameworks".to_string()],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        }
+    }
+
+    #[test]
+    fn default_layout_matches_compose() {
+        let registry = Registry::new(None);
+        let req = go_review();
+        let composition = compose_with_layout(&registry, &req, &Layout::default()).unwrap();
+        assert_eq!(composition.prompt, compose(&registry, &req).unwrap());
+    }
+
+    #[test]
+    fn layout_reorders_and_renames_sections() {
+        let registry = Registry::new(None);
+        let layout =
+            layout("order: [constraints, persona]\nheadings: {constraint: Rules, tone: ''}\n");
+        let prompt = compose_with_layout(&registry, &go_review(), &layout)
+            .unwrap()
+            .prompt;
+        assert!(prompt.starts_with("## Rules\n\n"), "{prompt}");
+        let persona = registry.get(Category::Persona, "backend-engineer").unwrap();
+        assert!(prompt.find(&persona.body).unwrap() < prompt.find("## Expertise").unwrap());
+        assert!(!prompt.contains("## Communication Style"));
+        let tone = registry.get(Category::Tone, "concise").unwrap();
+        assert!(prompt.ends_with(&tone.body));
+    }
+
+    #[test]
+    fn layout_adds_preamble_and_epilogue() {
+        let registry = Registry::new(None);
+        let layout = layout("preamble: context/code-review\nepilogue: constraints/no-frameworks\n");
+        let req = ComposeRequest {
+            constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+            ..go_review()
+        };
+        let composition = compose_with_layout(&registry, &req, &layout).unwrap();
+        let first = composition.spans.first().unwrap();
+        let last = composition.spans.last().unwrap();
+        assert_eq!(
+            (first.name.as_str(), &first.reason),
+            ("code-review", &Reason::Configured)
+        );
+        assert_eq!(first.start, 0);
+        assert_eq!(
+            (last.name.as_str(), &last.reason),
+            ("no-frameworks", &Reason::Configured)
+        );
+        assert_eq!(last.end, composition.prompt.len());
+    }
+
+    #[test]
+    fn layout_errors_on_missing_preamble() {
+        let registry = Registry::new(None);
+        let err = compose_with_layout(&registry, &go_review(), &layout("preamble: context/nope\n"))
+            .unwrap_err();
+        assert!(err.contains("Preamble"), "{err}");
+    }
+
+    #[test]
+    fn project_config_sets_default_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.yaml"),
+            "layout:\n  headings:\n    skill: Skills\n",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let prompt = compose(&registry, &go_review()).unwrap();
+        assert!(prompt.contains("## Skills\n\n"));
+        assert!(!prompt.contains("## Expertise"));
+    }
+
+    #[test]
+    fn custom_category_section_follows_placement() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.yaml"),
+            "categories:\n  - name: output-format\n    heading: Output Format\n    after: skill\n",
+        )
+        .unwrap();
+        let dir = tmp.path().join("output-format");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("json.md"), "Respond with JSON only.").unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+
+        let mut req = go_review();
+        req.custom
+            .insert("output-format".to_string(), vec!["json".to_string()]);
+        let prompt = compose(&registry, &req).unwrap();
+        let format = prompt
+            .find("## Output Format\n\nRespond with JSON only.")
+            .unwrap();
+        assert!(prompt.find("## Expertise").unwrap() < format);
+        assert!(format < prompt.find("## Communication Style").unwrap());
+
+        req.custom
+            .insert("missing".to_string(), vec!["x".to_string()]);
+        let err = compose(&registry, &req).unwrap_err();
+        assert!(err.contains("Unknown category 'missing'"), "{err}");
+    }
+
+    #[test]
+    fn parse_spec_collects_custom_categories() {
+        let (req, _) = parse_spec("designer?skills=css&output-format=json,yaml").unwrap();
+        assert_eq!(req.custom["output-format"], vec!["json", "yaml"]);
+    }
 }
@@ -0,0 +1,452 @@
+use crate::fragment::Category;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Settings read from `config.yaml` in the global and project layers. The
+/// project file is applied on top of the global one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub layout: Layout,
+    #[serde(default)]
+    pub categories: Vec<CategoryConfig>,
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Model targets that sibling variant files may be named after, such as
+    /// `claude` for `go.claude.md`, besides the built-in ones.
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+/// Targets a sibling file may name without declaring them in `targets`.
+const DEFAULT_TARGETS: &[&str] = &["small"];
+
+/// A custom fragment category, e.g. `output-format` for response-format
+/// instructions that are neither a tone nor a constraint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryConfig {
+    /// Singular name used in compose requests and frontmatter.
+    pub name: String,
+    /// Directory holding the fragments; defaults to `name`.
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Section heading; defaults to the title-cased name.
+    #[serde(default)]
+    pub heading: Option<String>,
+    /// Category this one is placed after; defaults to the end.
+    #[serde(default)]
+    pub after: Option<String>,
+}
+
+/// How `compose` arranges fragments into a prompt. Every field is optional;
+/// unset fields keep the built-in layout.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+    /// Section order by category (e.g. ["persona", "constraints", "skills"]).
+    /// Categories left out keep their default relative order after the listed ones.
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Heading overrides keyed by category (e.g. {"skill": "Skills"}). An empty
+    /// string hides that section's heading.
+    #[serde(default)]
+    pub headings: BTreeMap<String, String>,
+    /// Fragment rendered before everything else, as "category/name" (e.g. "context/house-rules").
+    #[serde(default)]
+    pub preamble: Option<String>,
+    /// Fragment rendered after everything else, as "category/name".
+    #[serde(default)]
+    pub epilogue: Option<String>,
+}
+
+/// Settings for `mimic lint`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    /// Per-rule level keyed by rule name (e.g. {"persona-has-level": "off"}).
+    /// Rules left out keep their default severity.
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+}
+
+/// Whether a lint rule runs, and how severe its findings are when it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+impl Config {
+    pub fn from_file(path: &Path) -> Result<Config, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{e}"))?;
+        if content.trim().is_empty() {
+            return Ok(Config::default());
+        }
+        serde_yaml::from_str(&content).map_err(|e| format!("{e}"))
+    }
+
+    /// Loads `config.yaml` from each layer directory, lowest priority first.
+    /// Unreadable files are reported and skipped.
+    pub fn load<'a>(dirs: impl IntoIterator<Item = &'a Path>) -> Config {
+        let mut config = Config::default();
+        for dir in dirs {
+            let path = dir.join("config.yaml");
+            if !path.is_file() {
+                continue;
+            }
+            match Config::from_file(&path) {
+                Ok(layer) => config.merge(layer),
+                Err(e) => eprintln!("mimic: warning: ignoring {}: {}", path.display(), e),
+            }
+        }
+        config
+    }
+
+    /// Applies `other` on top of `self`, field by field.
+    pub fn merge(&mut self, other: Config) {
+        for category in other.categories {
+            self.categories.retain(|c| c.name != category.name);
+            self.categories.push(category);
+        }
+        // Invalid categories are reported when the registry resolves them
+        let categories = self.resolve_categories().unwrap_or_default();
+        self.layout.merge(other.layout, &categories);
+        self.lint.rules.extend(other.lint.rules);
+        for target in other.targets {
+            if !self.targets.contains(&target) {
+                self.targets.push(target);
+            }
+        }
+    }
+
+    /// The targets sibling files may be named after: the built-in ones and
+    /// those declared in `targets`.
+    pub fn variant_targets(&self) -> Vec<String> {
+        let mut targets: Vec<String> = DEFAULT_TARGETS.iter().map(|t| t.to_string()).collect();
+        for target in &self.targets {
+            if !targets.contains(target) {
+                targets.push(target.clone());
+            }
+        }
+        targets
+    }
+
+    /// Defines the configured custom categories and returns every category
+    /// in its default placement order: the built-ins, with each custom
+    /// category inserted after its `after` category (or at the end).
+    pub fn resolve_categories(&self) -> Result<Vec<Category>, String> {
+        let mut all = Category::all().to_vec();
+        for def in &self.categories {
+            let dir = def.dir.as_deref().unwrap_or(&def.name);
+            let heading = def.heading.clone().unwrap_or_else(|| title_case(&def.name));
+            let category = Category::define(&def.name, dir, &heading)?;
+            let position = match def.after.as_deref() {
+                None => all.len(),
+                Some(after) => {
+                    let anchor = Category::lookup(after, &all)
+                        .and_then(|a| all.iter().position(|c| *c == a))
+                        .ok_or_else(|| {
+                            format!(
+                                "Category '{}' is placed after unknown category '{}'",
+                                def.name, after
+                            )
+                        })?;
+                    // Keep earlier custom categories anchored to the same spot first
+                    let mut position = anchor + 1;
+                    while position < all.len() && !all[position].is_builtin() {
+                        position += 1;
+                    }
+                    position
+                }
+            };
+            all.insert(position, category);
+        }
+        Ok(all)
+    }
+}
+
+fn title_case(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Layout {
+    /// Applies the fields set in `other` on top of `self`. Heading keys are
+    /// stored under the category's name, so `skills:` in `other` replaces
+    /// `skill:` in `self`.
+    pub fn merge(&mut self, other: Layout, categories: &[Category]) {
+        if !other.order.is_empty() {
+            self.order = other.order;
+        }
+        let canonical = |key: String| match Category::lookup(&key, categories) {
+            Some(category) => category.to_string(),
+            None => key,
+        };
+        self.headings = std::mem::take(&mut self.headings)
+            .into_iter()
+            .map(|(key, value)| (canonical(key), value))
+            .collect();
+        self.headings.extend(
+            other
+                .headings
+                .into_iter()
+                .map(|(key, value)| (canonical(key), value)),
+        );
+        if other.preamble.is_some() {
+            self.preamble = other.preamble;
+        }
+        if other.epilogue.is_some() {
+            self.epilogue = other.epilogue;
+        }
+    }
+
+    /// The section order with each of `categories` present exactly once.
+    pub fn resolved_order(&self, categories: &[Category]) -> Result<Vec<Category>, String> {
+        let mut order = Vec::new();
+        for name in &self.order {
+            let category = Category::lookup(name, categories)
+                .ok_or_else(|| format!("Unknown section '{}' in layout order", name))?;
+            if !order.contains(&category) {
+                order.push(category);
+            }
+        }
+        for category in categories {
+            if !order.contains(category) {
+                order.push(category.clone());
+            }
+        }
+        Ok(order)
+    }
+
+    /// The heading for `category`'s section, or `None` when it has none.
+    /// Heading keys name one of `categories`.
+    pub fn heading<'a>(
+        &'a self,
+        category: &'a Category,
+        categories: &[Category],
+    ) -> Result<Option<&'a str>, String> {
+        for (key, value) in &self.headings {
+            match Category::lookup(key, categories) {
+                Some(c) if c == *category => {
+                    return Ok(Some(value.as_str()).filter(|h| !h.is_empty()));
+                }
+                Some(_) => {}
+                None => return Err(format!("Unknown section '{}' in layout headings", key)),
+            }
+        }
+        Ok(default_heading(category))
+    }
+}
+
+fn default_heading(category: &Category) -> Option<&str> {
+    match category {
+        Category::Persona => None,
+        Category::Skill => Some("Expertise"),
+        Category::Context => Some("Context"),
+        Category::Tone => Some("Communication Style"),
+        Category::Constraint => Some("Constraints"),
+        Category::Example => Some("Examples"),
+        Category::Custom(def) => Some(&def.heading),
+    }
+}
+
+/// Parses a "category/name" fragment reference naming one of `categories`.
+pub fn parse_fragment_ref<'a>(
+    s: &'a str,
+    categories: &[Category],
+) -> Result<(Category, &'a str), String> {
+    s.split_once('/')
+        .and_then(|(cat, name)| Some((Category::lookup(cat, categories)?, name)))
+        .filter(|(_, name)| !name.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "Invalid fragment reference '{}' (expected \"category/name\")",
+                s
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_matches_builtin_order() {
+        let layout = Layout::default();
+        assert_eq!(
+            layout.resolved_order(Category::all()).unwrap(),
+            Category::all()
+        );
+        assert_eq!(
+            layout.heading(&Category::Persona, Category::all()).unwrap(),
+            None
+        );
+        assert_eq!(
+            layout.heading(&Category::Tone, Category::all()).unwrap(),
+            Some("Communication Style")
+        );
+    }
+
+    #[test]
+    fn partial_order_appends_remaining_categories() {
+        let layout = Layout {
+            order: vec!["constraints".to_string(), "persona".to_string()],
+            ..Layout::default()
+        };
+        assert_eq!(
+            layout.resolved_order(Category::all()).unwrap(),
+            vec![
+                Category::Constraint,
+                Category::Persona,
+                Category::Skill,
+                Category::Context,
+                Category::Tone,
+                Category::Example,
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_section_is_an_error() {
+        let layout = Layout {
+            order: vec!["glossary".to_string()],
+            ..Layout::default()
+        };
+        assert!(layout.resolved_order(Category::all()).is_err());
+    }
+
+    #[test]
+    fn empty_heading_hides_it() {
+        let mut layout = Layout::default();
+        layout.headings.insert("skill".to_string(), String::new());
+        layout
+            .headings
+            .insert("contexts".to_string(), "Situation".to_string());
+        assert_eq!(
+            layout.heading(&Category::Skill, Category::all()).unwrap(),
+            None
+        );
+        assert_eq!(
+            layout.heading(&Category::Context, Category::all()).unwrap(),
+            Some("Situation")
+        );
+    }
+
+    #[test]
+    fn lint_rule_levels_merge_per_rule() {
+        let mut base: Config = serde_yaml::from_str(
+            "lint:\n  rules:\n    persona-has-level: off\n    has-tags: error\n",
+        )
+        .unwrap();
+        let project: Config =
+            serde_yaml::from_str("lint:\n  rules: {has-tags: warning}\n").unwrap();
+        base.merge(project);
+        assert_eq!(base.lint.rules["persona-has-level"], RuleLevel::Off);
+        assert_eq!(base.lint.rules["has-tags"], RuleLevel::Warning);
+        assert!(serde_yaml::from_str::<Config>("lint:\n  rules: {has-tags: loud}\n").is_err());
+    }
+
+    #[test]
+    fn merge_overrides_only_set_fields() {
+        let mut base: Config = serde_yaml::from_str(
+            "layout:\n  order: [persona, tones]\n  headings: {skill: Skills}\n  preamble: context/house-rules\n",
+        )
+        .unwrap();
+        let project: Config = serde_yaml::from_str("layout:\n  headings: {tone: Voice}\n").unwrap();
+        base.merge(project);
+        assert_eq!(base.layout.order, vec!["persona", "tones"]);
+        assert_eq!(base.layout.headings.len(), 2);
+        assert_eq!(base.layout.preamble.as_deref(), Some("context/house-rules"));
+    }
+
+    #[test]
+    fn merge_matches_singular_and_plural_heading_keys() {
+        let mut base: Config = serde_yaml::from_str(
+            "categories:\n  - name: output-format\n    dir: output-formats\nlayout:\n  headings: {skill: Skills, output-formats: Formats}\n",
+        )
+        .unwrap();
+        let project: Config = serde_yaml::from_str(
+            "layout:\n  headings: {skills: Expertise, output-format: Output}\n",
+        )
+        .unwrap();
+        base.merge(project);
+        let categories = base.resolve_categories().unwrap();
+        let output = Category::lookup("output-format", &categories).unwrap();
+        let layout = &base.layout;
+        assert_eq!(layout.headings.len(), 2);
+        assert_eq!(
+            layout.heading(&Category::Skill, &categories).unwrap(),
+            Some("Expertise")
+        );
+        assert_eq!(
+            layout.heading(&output, &categories).unwrap(),
+            Some("Output")
+        );
+    }
+
+    #[test]
+    fn parse_fragment_ref_forms() {
+        assert_eq!(
+            parse_fragment_ref("context/house-rules", Category::all()).unwrap(),
+            (Category::Context, "house-rules")
+        );
+        assert_eq!(
+            parse_fragment_ref("constraints/no-frameworks", Category::all()).unwrap(),
+            (Category::Constraint, "no-frameworks")
+        );
+        assert!(parse_fragment_ref("house-rules", Category::all()).is_err());
+        assert!(parse_fragment_ref("context/", Category::all()).is_err());
+    }
+
+    #[test]
+    fn resolve_categories_places_custom_categories() {
+        let config: Config = serde_yaml::from_str(
+            "categories:\n  - name: checklist\n    after: skill\n  - name: output-format\n    dir: output-formats\n  - name: glossary\n    after: skills\n",
+        )
+        .unwrap();
+        let categories = config.resolve_categories().unwrap();
+        let names: Vec<String> = categories.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "persona",
+                "skill",
+                "checklist",
+                "glossary",
+                "context",
+                "tone",
+                "constraint",
+                "example",
+                "output-format",
+            ]
+        );
+        let output = Category::lookup("output-format", &categories).unwrap();
+        assert_eq!(output.dir_name(), "output-formats");
+        assert_eq!(
+            Layout::default().heading(&output, &categories).unwrap(),
+            Some("Output Format")
+        );
+    }
+
+    #[test]
+    fn resolve_categories_rejects_unknown_anchor() {
+        let config: Config =
+            serde_yaml::from_str("categories:\n  - name: orphan\n    after: nowhere\n").unwrap();
+        assert!(config.resolve_categories().is_err());
+    }
+}
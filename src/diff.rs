@@ -0,0 +1,326 @@
+use crate::compose::{self, ComposeRequest, Composition};
+use crate::fragment::Category;
+use crate::registry::Registry;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Lines of unchanged context shown around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Fragment-level and text-level differences between two compositions.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ComposeDiff {
+    /// Fragments only in the right-hand composition.
+    pub added: Vec<FragmentRef>,
+    /// Fragments only in the left-hand composition.
+    pub removed: Vec<FragmentRef>,
+    /// Unified diff of the rendered prompts; empty when they are identical.
+    pub unified: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct FragmentRef {
+    pub category: String,
+    pub name: String,
+}
+
+impl ComposeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.unified.is_empty()
+    }
+}
+
+/// Resolves a diff operand: a preset name, a persona name, or a compose spec
+/// such as `backend-engineer?skills=go,rust` (optionally prefixed with
+/// `mimic://compose/`). Specs are resolved against `registry`, so one that
+/// names another project with `project=` is rejected rather than silently
+/// composed from the wrong project.
+pub fn resolve_spec(registry: &Registry, spec: &str) -> Result<ComposeRequest, String> {
+    if let Some(preset) = registry.preset(spec) {
+        return Ok(preset.to_request());
+    }
+    let bare = spec.strip_prefix("mimic://compose/").unwrap_or(spec);
+    let (req, project) =
+        compose::parse_spec(bare).ok_or_else(|| format!("Invalid compose spec '{}'", spec))?;
+    if let Some(project) = project {
+        return Err(format!(
+            "Spec '{}' names project '{}'; both sides are composed from the same project, so drop `project=` and choose the project instead",
+            spec, project
+        ));
+    }
+    if !bare.contains('?') && registry.get(Category::Persona, &req.persona).is_none() {
+        return Err(format!("'{}' is neither a preset nor a persona", spec));
+    }
+    Ok(req)
+}
+
+pub fn diff(
+    registry: &Registry,
+    left: &ComposeRequest,
+    right: &ComposeRequest,
+    left_label: &str,
+    right_label: &str,
+) -> Result<ComposeDiff, String> {
+    let old = compose::compose_explained(registry, left)?;
+    let new = compose::compose_explained(registry, right)?;
+    let old_refs = fragment_refs(&old);
+    let new_refs = fragment_refs(&new);
+    Ok(ComposeDiff {
+        added: new_refs
+            .iter()
+            .filter(|r| !old_refs.contains(r))
+            .cloned()
+            .collect(),
+        removed: old_refs
+            .iter()
+            .filter(|r| !new_refs.contains(r))
+            .cloned()
+            .collect(),
+        unified: unified_diff(&old.prompt, &new.prompt, left_label, right_label),
+    })
+}
+
+fn fragment_refs(composition: &Composition) -> Vec<FragmentRef> {
+    composition
+        .spans
+        .iter()
+        .map(|s| FragmentRef {
+            category: s.category.clone(),
+            name: s.name.clone(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Line-based unified diff (`diff -u` style). Returns an empty string when
+/// the inputs are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..ops.len()).filter(|&i| ops[i].0 != Op::Equal).collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // 0-based line numbers in old/new at the start of each op
+    let mut old_pos = Vec::with_capacity(ops.len());
+    let mut new_pos = Vec::with_capacity(ops.len());
+    let (mut o, mut n) = (0, 0);
+    for (op, _) in &ops {
+        old_pos.push(o);
+        new_pos.push(n);
+        if *op != Op::Insert {
+            o += 1;
+        }
+        if *op != Op::Delete {
+            n += 1;
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut k = 0;
+    while k < changes.len() {
+        let start = changes[k].saturating_sub(CONTEXT_LINES);
+        let mut last = changes[k];
+        while k + 1 < changes.len() && changes[k + 1] - last <= 2 * CONTEXT_LINES + 1 {
+            k += 1;
+            last = changes[k];
+        }
+        k += 1;
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+        let hunk = &ops[start..end];
+
+        let old_count = hunk.iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_count = hunk.iter().filter(|(op, _)| *op != Op::Delete).count();
+        // An empty range is reported as starting at the line before it
+        let old_start = old_pos[start] + usize::from(old_count > 0);
+        let new_start = new_pos[start] + usize::from(new_count > 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for (op, line) in hunk {
+            let prefix = match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Longest-common-subsequence edit script. Prompts are a few hundred lines
+/// at most, so the quadratic table is fine.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push((Op::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push((Op::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|l| (Op::Delete, *l)));
+    ops.extend(new[j..].iter().map(|l| (Op::Insert, *l)));
+    ops
+}
+
+/// Entry point for `mimic diff`. Exits 0 when the compositions match, 1 when
+/// they differ, and 2 on error, mirroring `diff(1)`.
+pub fn run(project_dir: Option<PathBuf>, left: &str, right: &str) -> i32 {
+    let registry = Registry::new(project_dir);
+    let result = resolve_spec(&registry, left)
+        .and_then(|l| resolve_spec(&registry, right).map(|r| (l, r)))
+        .and_then(|(l, r)| diff(&registry, &l, &r, left, right));
+    let result = match result {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("mimic: {}", e);
+            return 2;
+        }
+    };
+
+    if result.is_empty() {
+        println!("No differences.");
+        return 0;
+    }
+    if !result.added.is_empty() || !result.removed.is_empty() {
+        println!("Fragments:");
+        for r in &result.removed {
+            println!("  - {}/{}", r.category, r.name);
+        }
+        for r in &result.added {
+            println!("  + {}/{}", r.category, r.name);
+        }
+        println!();
+    }
+    print!("{}", result.unified);
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(persona: &str, skills: &[&str]) -> ComposeRequest {
+        ComposeRequest {
+            persona: persona.to_string(),
+            skills: skills.iter().map(|s| s.to_string()).collect(),
+            contexts: vec![],
+            tones: vec![],
+            constraints: vec![],
+            examples: vec![],
+            target: None,
+            locale: None,
+            replace_deprecated: false,
+            custom: Default::default(),
+        }
+    }
+
+    #[test]
+    fn unified_diff_identical_is_empty() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "l", "r"), "");
+    }
+
+    #[test]
+    fn unified_diff_single_change() {
+        let diff = unified_diff("a\nb\nc\n", "a\nx\nc\n", "l", "r");
+        assert_eq!(diff, "--- l\n+++ r\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n");
+    }
+
+    #[test]
+    fn unified_diff_splits_distant_hunks() {
+        let lines: Vec<String> = (1..=20).map(|i| format!("line {i}")).collect();
+        let old = lines.join("\n");
+        let mut changed = lines.clone();
+        changed[1] = "second".to_string();
+        changed[18] = "nineteenth".to_string();
+        let new = changed.join("\n");
+        let diff = unified_diff(&old, &new, "l", "r");
+        assert_eq!(diff.matches("@@ -").count(), 2, "{diff}");
+        assert!(diff.contains("@@ -1,5 +1,5 @@"), "{diff}");
+        assert!(diff.contains("@@ -16,5 +16,5 @@"), "{diff}");
+    }
+
+    #[test]
+    fn unified_diff_pure_insertion() {
+        let diff = unified_diff("", "a\n", "l", "r");
+        assert_eq!(diff, "--- l\n+++ r\n@@ -0,0 +1,1 @@\n+a\n");
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed_fragments() {
+        let registry = Registry::new(None);
+        let left = request("backend-engineer", &["go", "postgresql"]);
+        let right = request("backend-engineer", &["go", "rust"]);
+        let result = diff(&registry, &left, &right, "left", "right").unwrap();
+        let skill = |name: &str| FragmentRef {
+            category: "skill".to_string(),
+            name: name.to_string(),
+        };
+        assert_eq!(result.added, vec![skill("rust")]);
+        assert_eq!(result.removed, vec![skill("postgresql")]);
+        assert!(result.unified.starts_with("--- left\n+++ right\n"));
+    }
+
+    #[test]
+    fn diff_same_request_is_empty() {
+        let registry = Registry::new(None);
+        let req = request("backend-engineer", &["go"]);
+        assert!(diff(&registry, &req, &req, "a", "b").unwrap().is_empty());
+    }
+
+    #[test]
+    fn resolve_spec_prefers_presets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let presets = tmp.path().join("presets");
+        std::fs::create_dir_all(&presets).unwrap();
+        std::fs::write(
+            presets.join("go-review.yaml"),
+            "persona: backend-engineer\nskills: [go]\n",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+
+        let req = resolve_spec(&registry, "go-review").unwrap();
+        assert_eq!(req.skills, vec!["go"]);
+        let req = resolve_spec(&registry, "mimic://compose/designer?skills=css").unwrap();
+        assert_eq!(req.persona, "designer");
+        let err = resolve_spec(&registry, "no-such-thing").unwrap_err();
+        assert!(err.contains("neither a preset nor a persona"), "{err}");
+        let err = resolve_spec(&registry, "designer?skills=css&project=web").unwrap_err();
+        assert!(err.contains("names project 'web'"), "{err}");
+    }
+}
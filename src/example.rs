@@ -0,0 +1,257 @@
+//! Few-shot example fragments: user/assistant exchanges that `compose`
+//! renders inline in the system prompt or as real conversation turns.
+//!
+//! An example body is either fenced blocks tagged with a role:
+//!
+//! ````markdown
+//! ```user
+//! Review this function.
+//! ```
+//!
+//! ```assistant
+//! The loop never terminates when `n` is negative.
+//! ```
+//! ````
+//!
+//! or a YAML list of single-role entries:
+//!
+//! ```yaml
+//! - user: Review this function.
+//! - assistant: The loop never terminates when `n` is negative.
+//! ```
+//!
+//! Fences follow CommonMark, so a turn containing its own code blocks can be
+//! wrapped in four backticks. Text outside the fences is ignored.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+impl Role {
+    fn from_name(s: &str) -> Option<Role> {
+        match s {
+            "user" => Some(Role::User),
+            "assistant" => Some(Role::Assistant),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::User => write!(f, "user"),
+            Role::Assistant => write!(f, "assistant"),
+        }
+    }
+}
+
+/// One message of an example exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Turn {
+    pub role: Role,
+    pub content: String,
+}
+
+/// Parses the turns of an example body, in order.
+pub fn parse_turns(body: &str) -> Result<Vec<Turn>, String> {
+    let turns = if has_role_fence(body) {
+        parse_fenced(body)?
+    } else {
+        parse_yaml(body)?
+    };
+    if turns.is_empty() {
+        return Err("no user or assistant turns found".to_string());
+    }
+    Ok(turns)
+}
+
+/// Checks that `turns` alternate between user and assistant, starting with
+/// the user and ending with the assistant, as chat APIs expect.
+pub fn check_alternation(turns: &[Turn]) -> Result<(), String> {
+    let mut expected = Role::User;
+    for (i, turn) in turns.iter().enumerate() {
+        if turn.role != expected {
+            return Err(format!(
+                "turn {} is from the {} but the {} was expected",
+                i + 1,
+                turn.role,
+                expected
+            ));
+        }
+        expected = match expected {
+            Role::User => Role::Assistant,
+            Role::Assistant => Role::User,
+        };
+    }
+    if expected == Role::Assistant {
+        return Err("the last turn is from the user; end with the assistant's reply".to_string());
+    }
+    Ok(())
+}
+
+/// Renders an example for the system prompt. Bodies that aren't valid
+/// exchanges are used verbatim.
+pub fn render_inline(body: &str) -> String {
+    match parse_turns(body) {
+        Ok(turns) => turns
+            .iter()
+            .map(|t| {
+                let speaker = match t.role {
+                    Role::User => "User",
+                    Role::Assistant => "Assistant",
+                };
+                format!("**{}:** {}", speaker, t.content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Err(_) => body.to_string(),
+    }
+}
+
+fn has_role_fence(body: &str) -> bool {
+    body.lines()
+        .filter_map(opening_fence)
+        .any(|(_, info)| Role::from_name(info).is_some())
+}
+
+/// Returns the backtick count and info string of a fence-opening line.
+fn opening_fence(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let ticks = trimmed.len() - trimmed.trim_start_matches('`').len();
+    (ticks >= 3).then(|| (ticks, trimmed[ticks..].trim()))
+}
+
+fn is_closing_fence(line: &str, ticks: usize) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() >= ticks && trimmed.chars().all(|c| c == '`')
+}
+
+fn parse_fenced(body: &str) -> Result<Vec<Turn>, String> {
+    let mut turns = Vec::new();
+    let mut lines = body.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let Some((ticks, info)) = opening_fence(line) else {
+            continue;
+        };
+        let role = Role::from_name(info);
+        let mut content = Vec::new();
+        let mut closed = false;
+        for (_, line) in lines.by_ref() {
+            if is_closing_fence(line, ticks) {
+                closed = true;
+                break;
+            }
+            content.push(line);
+        }
+        if !closed {
+            return Err(format!("unclosed code fence opened on line {}", number + 1));
+        }
+        // Other fenced blocks are notes for authors, like the prose around them
+        if let Some(role) = role {
+            turns.push(Turn {
+                role,
+                content: content.join("\n").trim().to_string(),
+            });
+        }
+    }
+    Ok(turns)
+}
+
+fn parse_yaml(body: &str) -> Result<Vec<Turn>, String> {
+    let entries: Vec<serde_yaml::Mapping> = serde_yaml::from_str(body).map_err(|_| {
+        "expected ```user / ```assistant fenced blocks or a YAML list of `user:` / `assistant:` entries"
+            .to_string()
+    })?;
+    let mut turns = Vec::new();
+    for entry in entries {
+        for (key, value) in entry {
+            let key = key.as_str().unwrap_or_default();
+            let role =
+                Role::from_name(key).ok_or_else(|| format!("unknown role `{}` in example", key))?;
+            let content = value
+                .as_str()
+                .ok_or_else(|| format!("`{}` turn must be a string", role))?;
+            turns.push(Turn {
+                role,
+                content: content.trim().to_string(),
+            });
+        }
+    }
+    Ok(turns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: Role, content: &str) -> Turn {
+        Turn {
+            role,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_fenced_turns() {
+        let body = "A short exchange.\n\n```user\nHi\n```\n\n```assistant\nHello!\n```\n";
+        assert_eq!(
+            parse_turns(body).unwrap(),
+            vec![turn(Role::User, "Hi"), turn(Role::Assistant, "Hello!")]
+        );
+    }
+
+    #[test]
+    fn longer_fences_keep_nested_code_blocks() {
+        let body =
+            "````user\nFix:\n```rust\nlet x = 1\n```\n````\n````assistant\nAdd a semicolon.\n````";
+        let turns = parse_turns(body).unwrap();
+        assert_eq!(turns[0].content, "Fix:\n```rust\nlet x = 1\n```");
+        assert_eq!(turns[1], turn(Role::Assistant, "Add a semicolon."));
+    }
+
+    #[test]
+    fn parses_yaml_turns() {
+        let body = "- user: Hi\n- assistant: |\n    Hello!\n    How can I help?\n";
+        assert_eq!(
+            parse_turns(body).unwrap(),
+            vec![
+                turn(Role::User, "Hi"),
+                turn(Role::Assistant, "Hello!\nHow can I help?")
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bodies_without_turns() {
+        assert!(parse_turns("Just some prose.").is_err());
+        assert!(parse_turns("```user\nHi\n")
+            .unwrap_err()
+            .contains("unclosed"));
+        assert!(parse_turns("- system: Hi\n")
+            .unwrap_err()
+            .contains("system"));
+    }
+
+    #[test]
+    fn alternation() {
+        let good = [turn(Role::User, "a"), turn(Role::Assistant, "b")];
+        assert!(check_alternation(&good).is_ok());
+        assert!(check_alternation(&good[..1]).is_err());
+        assert!(check_alternation(&good[1..]).is_err());
+    }
+
+    #[test]
+    fn render_inline_labels_speakers() {
+        let body = "```user\nHi\n```\n```assistant\nHello!\n```";
+        assert_eq!(render_inline(body), "**User:** Hi\n\n**Assistant:** Hello!");
+        assert_eq!(render_inline("plain text"), "plain text");
+    }
+}
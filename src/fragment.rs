@@ -1,18 +1,38 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Category {
     Persona,
     Skill,
     Context,
     Tone,
     Constraint,
+    /// Worked user/assistant exchanges, rendered inline or as prior turns.
+    Example,
+    /// A project-defined category declared under `categories:` in config.yaml.
+    Custom(Arc<CategoryDef>),
+}
+
+/// Definition of a custom category. Each registry holds its own, so two
+/// projects can define the same name differently.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CategoryDef {
+    /// Singular name used in compose requests and frontmatter (e.g. "output-format").
+    pub name: String,
+    /// Directory holding the category's fragments (e.g. "output-formats").
+    pub dir: String,
+    /// Section heading used when composing.
+    pub heading: String,
 }
 
 impl Category {
+    /// The built-in categories. Custom categories are per registry; see
+    /// `Registry::categories`.
     pub fn all() -> &'static [Category] {
         &[
             Category::Persona,
@@ -20,19 +40,77 @@ impl Category {
             Category::Context,
             Category::Tone,
             Category::Constraint,
+            Category::Example,
         ]
     }
 
-    pub fn dir_name(&self) -> &'static str {
+    /// Declares a custom category after checking that its name and
+    /// directory are valid and don't clash with a built-in.
+    pub fn define(name: &str, dir: &str, heading: &str) -> Result<Category, String> {
+        let valid = |s: &str| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        };
+        if !valid(name) || !valid(dir) {
+            return Err(format!(
+                "Invalid category '{}' (dir '{}'): use lowercase letters, digits, '-' or '_'",
+                name, dir
+            ));
+        }
+        let clashes = Category::all().iter().any(|c| {
+            let builtin = [c.to_string(), c.dir_name().to_string()];
+            builtin.iter().any(|b| b == name || b == dir)
+        });
+        if clashes || matches!(dir, "presets") {
+            return Err(format!(
+                "Category '{}' (dir '{}') clashes with a built-in name",
+                name, dir
+            ));
+        }
+
+        Ok(Category::Custom(Arc::new(CategoryDef {
+            name: name.to_string(),
+            dir: dir.to_string(),
+            heading: heading.to_string(),
+        })))
+    }
+
+    /// Finds a category by singular or plural built-in name, or by the name
+    /// or directory of one of the custom categories in `categories`.
+    pub fn lookup(name: &str, categories: &[Category]) -> Option<Category> {
+        Category::from_name(name)
+            .or_else(|| categories.iter().find(|c| c.is_custom_named(name)).cloned())
+    }
+
+    /// Finds a category by directory among the built-ins and `categories`.
+    pub fn lookup_dir(dir: &str, categories: &[Category]) -> Option<Category> {
+        Category::from_dir_name(dir)
+            .or_else(|| categories.iter().find(|c| c.dir_name() == dir).cloned())
+    }
+
+    fn is_custom_named(&self, name: &str) -> bool {
+        matches!(self, Category::Custom(def) if def.name == name || def.dir == name)
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, Category::Custom(_))
+    }
+
+    pub fn dir_name(&self) -> &str {
         match self {
             Category::Persona => "personas",
             Category::Skill => "skills",
             Category::Context => "contexts",
             Category::Tone => "tones",
             Category::Constraint => "constraints",
+            Category::Example => "examples",
+            Category::Custom(def) => &def.dir,
         }
     }
 
+    /// The built-in category stored in directory `name`; see `lookup_dir`
+    /// for custom categories.
     pub fn from_dir_name(name: &str) -> Option<Category> {
         match name {
             "personas" => Some(Category::Persona),
@@ -40,11 +118,13 @@ impl Category {
             "contexts" => Some(Category::Context),
             "tones" => Some(Category::Tone),
             "constraints" => Some(Category::Constraint),
+            "examples" => Some(Category::Example),
             _ => None,
         }
     }
 
-    /// Accepts both singular ("skill") and plural ("skills") forms.
+    /// Accepts both singular ("skill") and plural ("skills") forms of the
+    /// built-in categories; see `lookup` for custom categories.
     pub fn from_name(name: &str) -> Option<Category> {
         match name {
             "persona" | "personas" => Some(Category::Persona),
@@ -52,6 +132,7 @@ impl Category {
             "context" | "contexts" => Some(Category::Context),
             "tone" | "tones" => Some(Category::Tone),
             "constraint" | "constraints" => Some(Category::Constraint),
+            "example" | "examples" => Some(Category::Example),
             _ => None,
         }
     }
@@ -65,10 +146,18 @@ impl fmt::Display for Category {
             Category::Context => write!(f, "context"),
             Category::Tone => write!(f, "tone"),
             Category::Constraint => write!(f, "constraint"),
+            Category::Example => write!(f, "example"),
+            Category::Custom(def) => write!(f, "{}", def.name),
         }
     }
 }
 
+impl Serialize for Category {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Frontmatter {
     #[serde(default)]
@@ -83,9 +172,50 @@ pub struct Frontmatter {
     pub skill_groups: Vec<String>,
     #[serde(default)]
     pub category: Option<String>,
+    /// Alternative bodies keyed by target (e.g. `small` for short-context models).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variants: BTreeMap<String, String>,
+    /// Language of the body as a BCP 47 tag (e.g. `ja`, `pt-BR`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Version of the fragment's content (e.g. `2.1`).
+    #[serde(
+        default,
+        deserialize_with = "scalar_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub version: Option<String>,
+    /// Version or date the fragment was deprecated in.
+    #[serde(
+        default,
+        deserialize_with = "scalar_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Fragment of the same category to use instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Accepts unquoted numbers and dates (`version: 2.1`, `since: 2024-05-01`)
+/// as strings.
+fn scalar_string<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<serde_yaml::Value>::deserialize(d)?;
+    match value {
+        None | Some(serde_yaml::Value::Null) => Ok(None),
+        Some(serde_yaml::Value::String(s)) => Ok(Some(s)),
+        Some(serde_yaml::Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(serde_yaml::Value::Bool(b)) => Ok(Some(b.to_string())),
+        Some(_) => Err(serde::de::Error::custom("expected a string or number")),
+    }
+}
+
+/// Layer a fragment was loaded from, ordered lowest priority first.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Source {
     BuiltIn,
@@ -104,6 +234,30 @@ pub struct Fragment {
     pub skill_groups: Vec<String>,
     pub body: String,
     pub source: Source,
+    /// File the fragment was loaded from; `None` for built-ins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Lower-priority layers that define a fragment with the same name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shadows: Vec<Source>,
+    /// Alternative bodies keyed by target, from `variants:` frontmatter or
+    /// sibling files such as `go.small.md`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub variants: BTreeMap<String, String>,
+    /// Language of the body, when declared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Translations keyed by locale, from sibling files such as `go.ja.md`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, Fragment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
 }
 
 impl Fragment {
@@ -123,16 +277,173 @@ impl Fragment {
             skill_groups: frontmatter.skill_groups,
             body,
             source,
+            path: None,
+            shadows: vec![],
+            variants: frontmatter
+                .variants
+                .into_iter()
+                .map(|(k, v)| (k, v.trim().to_string()))
+                .collect(),
+            locale: frontmatter.locale,
+            translations: BTreeMap::new(),
+            version: frontmatter.version,
+            since: frontmatter.since,
+            deprecated: frontmatter.deprecated,
+            replaced_by: frontmatter.replaced_by,
+        }
+    }
+
+    /// The translation to use for `locale`, falling back to this fragment.
+    /// An exact tag wins; otherwise a body in the same language (`pt` for
+    /// `pt-BR`, or the reverse) is used.
+    pub fn localized(&self, locale: Option<&str>) -> &Fragment {
+        let Some(locale) = locale else {
+            return self;
+        };
+        let candidates = std::iter::once(self).chain(self.translations.values());
+        let tag = |f: &Fragment| f.locale.clone().unwrap_or_default();
+        if let Some(exact) = candidates
+            .clone()
+            .find(|f| tag(f).eq_ignore_ascii_case(locale))
+        {
+            return exact;
+        }
+        let language = primary_language(locale);
+        candidates
+            .filter(|f| f.locale.is_some())
+            .find(|f| primary_language(&tag(f)).eq_ignore_ascii_case(language))
+            .unwrap_or(self)
+    }
+
+    /// Picks the body for `target`: the variant named exactly `target`, else
+    /// the longest variant that `target` extends with a `-` suffix (so
+    /// `claude-haiku` falls back to `claude`), else the default body.
+    /// Returns the body and the variant it came from.
+    pub fn body_for(&self, target: Option<&str>) -> (&str, Option<&str>) {
+        let Some(target) = target else {
+            return (&self.body, None);
+        };
+        let best = self
+            .variants
+            .iter()
+            .filter(|(name, _)| {
+                target == name.as_str()
+                    || target
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with('-'))
+            })
+            .max_by_key(|(name, _)| name.len());
+        match best {
+            Some((name, body)) => (body, Some(name)),
+            None => (&self.body, None),
         }
     }
 
     pub fn from_file(path: &Path, category: Category, source: Source) -> Option<Fragment> {
         let name = path.file_stem()?.to_str()?;
         let content = std::fs::read_to_string(path).ok()?;
-        Some(Fragment::parse(&content, name, category, source))
+        let mut frag = Fragment::parse(&content, name, category, source);
+        frag.path = Some(path.to_path_buf());
+        Some(frag)
     }
 }
 
+impl Frontmatter {
+    /// Renders a fragment file: this frontmatter in the same flow style the
+    /// built-ins use, followed by `body`. Empty fields are omitted.
+    pub fn render(&self, body: &str) -> String {
+        let mut out = String::from("---\n");
+        if let Some(ref c) = self.category {
+            out.push_str(&format!("category: {}\n", yaml_scalar(c, false)));
+        }
+        if let Some(ref d) = self.description {
+            out.push_str(&format!("description: {}\n", yaml_scalar(d, false)));
+        }
+        if !self.tags.is_empty() {
+            out.push_str(&format!("tags: {}\n", yaml_flow_list(&self.tags)));
+        }
+        if let Some(ref g) = self.group {
+            out.push_str(&format!("group: {}\n", yaml_scalar(g, false)));
+        }
+        if let Some(ref l) = self.level {
+            out.push_str(&format!("level: {}\n", yaml_scalar(l, false)));
+        }
+        if !self.skill_groups.is_empty() {
+            out.push_str(&format!(
+                "skill_groups: {}\n",
+                yaml_flow_list(&self.skill_groups)
+            ));
+        }
+        if let Some(ref l) = self.locale {
+            out.push_str(&format!("locale: {}\n", yaml_scalar(l, false)));
+        }
+        if let Some(ref v) = self.version {
+            out.push_str(&format!("version: {}\n", yaml_scalar(v, false)));
+        }
+        if let Some(ref s) = self.since {
+            out.push_str(&format!("since: {}\n", yaml_scalar(s, false)));
+        }
+        if self.deprecated {
+            out.push_str("deprecated: true\n");
+        }
+        if let Some(ref r) = self.replaced_by {
+            out.push_str(&format!("replaced_by: {}\n", yaml_scalar(r, false)));
+        }
+        if !self.variants.is_empty() {
+            out.push_str("variants:\n");
+            for (target, body) in &self.variants {
+                out.push_str(&format!(
+                    "  {}: {}\n",
+                    yaml_scalar(target, false),
+                    yaml_block(body.trim())
+                ));
+            }
+        }
+        out.push_str("---\n");
+        out.push_str(body.trim());
+        out.push('\n');
+        out
+    }
+}
+
+/// Emits `s` as a plain YAML scalar when it round-trips unchanged, otherwise
+/// as a double-quoted (JSON-compatible) string.
+fn yaml_scalar(s: &str, in_flow: bool) -> String {
+    let flow_unsafe = in_flow && s.contains([',', '[', ']', '{', '}']);
+    let plain_ok = !flow_unsafe
+        && serde_yaml::from_str::<serde_yaml::Value>(s)
+            .ok()
+            .is_some_and(|v| v.as_str() == Some(s));
+    if plain_ok {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).unwrap_or_default()
+    }
+}
+
+/// Emits multi-line text as a literal block scalar indented under a map key.
+fn yaml_block(s: &str) -> String {
+    if s.starts_with(' ') || !s.contains('\n') {
+        return yaml_scalar(s, false);
+    }
+    let lines: Vec<String> = s
+        .lines()
+        .map(|l| {
+            if l.is_empty() {
+                String::new()
+            } else {
+                format!("    {l}")
+            }
+        })
+        .collect();
+    format!("|-\n{}", lines.join("\n"))
+}
+
+fn yaml_flow_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|i| yaml_scalar(i, true)).collect();
+    format!("[{}]", items.join(", "))
+}
+
 fn parse_frontmatter(content: &str) -> (Frontmatter, &str) {
     let trimmed = content.trim_start();
     if !trimmed.starts_with("---") {
@@ -150,15 +461,86 @@ fn parse_frontmatter(content: &str) -> (Frontmatter, &str) {
     }
 }
 
+/// Frontmatter that failed to parse. `line` and `column` are 1-based
+/// positions in the whole fragment file, when the YAML parser reports one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontmatterError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl FrontmatterError {
+    /// Converts a YAML error whose positions are relative to the frontmatter,
+    /// which starts on the opening `---` at `fence_line`.
+    fn from_yaml(e: serde_yaml::Error, fence_line: usize) -> Self {
+        let location = e.location();
+        FrontmatterError {
+            message: shift_line_numbers(&e.to_string(), fence_line - 1),
+            line: location.as_ref().map(|l| l.line() + fence_line - 1),
+            // The YAML's first line shares the fence's line, after the `---`
+            column: location
+                .as_ref()
+                .map(|l| l.column() + if l.line() == 1 { 3 } else { 0 }),
+        }
+    }
+}
+
+impl fmt::Display for FrontmatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Adds `offset` to every "line N" in a YAML error message.
+fn shift_line_numbers(message: &str, offset: usize) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(i) = rest.find("line ") {
+        let (before, after) = rest.split_at(i + "line ".len());
+        out.push_str(before);
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let whole_word = !rest[..i].ends_with(|c: char| c.is_alphanumeric());
+        match after[..digits].parse::<usize>() {
+            Ok(n) if whole_word => out.push_str(&(n + offset).to_string()),
+            _ => out.push_str(&after[..digits]),
+        }
+        rest = &after[digits..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Frontmatter keys mimic understands; anything else is reported as unknown.
+pub const KNOWN_FIELDS: &[&str] = &[
+    "description",
+    "tags",
+    "group",
+    "level",
+    "skill_groups",
+    "category",
+    "variants",
+    "locale",
+    "version",
+    "since",
+    "deprecated",
+    "replaced_by",
+];
+
 /// Strict frontmatter parser that returns errors and unknown fields.
-/// Returns `(frontmatter, unknown_field_names, body)` or an error string.
+/// Returns `(frontmatter, unknown_field_names, body)`, or a
+/// [`FrontmatterError`] locating the invalid YAML in the file.
 pub fn parse_frontmatter_strict(
     content: &str,
-) -> Result<(Frontmatter, Vec<String>, String), String> {
+) -> Result<(Frontmatter, Vec<String>, String), FrontmatterError> {
     let trimmed = content.trim_start();
     if !trimmed.starts_with("---") {
         return Ok((Frontmatter::default(), vec![], content.to_string()));
     }
+    let fence_line = content[..content.len() - trimmed.len()]
+        .matches('\n')
+        .count()
+        + 1;
 
     let after_first_fence = &trimmed[3..];
     let Some(end) = after_first_fence.find("\n---") else {
@@ -169,22 +551,15 @@ pub fn parse_frontmatter_strict(
     let body = &after_first_fence[end + 4..];
 
     // First parse as Value to detect unknown fields
-    let value: serde_yaml::Value = serde_yaml::from_str(yaml).map_err(|e| format!("{e}"))?;
-
-    let known_fields: &[&str] = &[
-        "description",
-        "tags",
-        "group",
-        "level",
-        "skill_groups",
-        "category",
-    ];
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(yaml).map_err(|e| FrontmatterError::from_yaml(e, fence_line))?;
+
     let mut unknown_fields = Vec::new();
 
     if let serde_yaml::Value::Mapping(ref map) = value {
         for key in map.keys() {
             if let serde_yaml::Value::String(ref k) = key {
-                if !known_fields.contains(&k.as_str()) {
+                if !KNOWN_FIELDS.contains(&k.as_str()) {
                     unknown_fields.push(k.clone());
                 }
             }
@@ -192,11 +567,58 @@ pub fn parse_frontmatter_strict(
     }
 
     // Now deserialize into Frontmatter
-    let fm: Frontmatter = serde_yaml::from_value(value).map_err(|e| format!("{e}"))?;
+    let fm: Frontmatter =
+        serde_yaml::from_value(value).map_err(|e| FrontmatterError::from_yaml(e, fence_line))?;
 
     Ok((fm, unknown_fields, body.to_string()))
 }
 
+/// ISO 639-1 language codes.
+const LANGUAGES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+    "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+    "de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+    "ia", "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj",
+    "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb",
+    "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi",
+    "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti",
+    "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo",
+    "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// Whether a file-name suffix reads as a locale tag: an ISO 639-1 language,
+/// optionally followed by a region or script (`ja`, `pt-BR`, `es-419`,
+/// `zh-Hant`).
+pub fn is_locale_tag(s: &str) -> bool {
+    let (language, subtag) = match s.split_once('-') {
+        Some((language, subtag)) => (language, Some(subtag)),
+        None => (s, None),
+    };
+    LANGUAGES.contains(&language)
+        && subtag.is_none_or(|t| match t.len() {
+            2 | 4 => t.bytes().all(|b| b.is_ascii_alphabetic()),
+            3 => t.bytes().all(|b| b.is_ascii_digit()),
+            _ => false,
+        })
+}
+
+/// Whether a sibling file's suffix names one of `targets`, exactly or
+/// extended with a `-` suffix (`claude-haiku` for `claude`).
+pub fn is_target_suffix(suffix: &str, targets: &[String]) -> bool {
+    targets.iter().any(|t| {
+        suffix
+            .strip_prefix(t.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    })
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
 fn first_line(s: &str) -> &str {
     s.lines().next().unwrap_or("")
 }
@@ -284,7 +706,7 @@ You are a senior backend engineer."#;
     fn category_dir_roundtrip() {
         for cat in Category::all() {
             let dir = cat.dir_name();
-            assert_eq!(Category::from_dir_name(dir), Some(*cat));
+            assert_eq!(Category::from_dir_name(dir), Some(cat.clone()));
         }
     }
 
@@ -326,6 +748,7 @@ You are a senior backend engineer."#;
         assert_eq!(frag.description, "A test skill");
         assert_eq!(frag.tags, vec!["rust"]);
         assert_eq!(frag.group.as_deref(), Some("backend"));
+        assert_eq!(frag.path.as_deref(), Some(path.as_path()));
     }
 
     #[test]
@@ -338,6 +761,151 @@ You are a senior backend engineer."#;
         assert!(result.is_none());
     }
 
+    #[test]
+    fn render_matches_builtin_style() {
+        let fm = Frontmatter {
+            description: Some("Go programming language expertise".to_string()),
+            tags: vec!["go".to_string(), "golang".to_string()],
+            group: Some("backend".to_string()),
+            ..Frontmatter::default()
+        };
+        assert_eq!(
+            fm.render("You write idiomatic Go.\n\n"),
+            "---\ndescription: Go programming language expertise\ntags: [go, golang]\ngroup: backend\n---\nYou write idiomatic Go.\n"
+        );
+    }
+
+    #[test]
+    fn render_quotes_special_values_and_roundtrips() {
+        let fm = Frontmatter {
+            description: Some("Rule: never # guess".to_string()),
+            tags: vec!["a,b".to_string(), "true".to_string()],
+            level: Some("senior".to_string()),
+            skill_groups: vec!["backend".to_string()],
+            category: Some("persona".to_string()),
+            ..Frontmatter::default()
+        };
+        let rendered = fm.render("Body.");
+        let (parsed, unknown, body) = parse_frontmatter_strict(&rendered).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(parsed.description, fm.description);
+        assert_eq!(parsed.tags, fm.tags);
+        assert_eq!(parsed.level, fm.level);
+        assert_eq!(parsed.skill_groups, fm.skill_groups);
+        assert_eq!(parsed.category, fm.category);
+        assert_eq!(body.trim(), "Body.");
+    }
+
+    #[test]
+    fn render_roundtrips_variants() {
+        let mut fm = Frontmatter::default();
+        fm.variants.insert(
+            "small".to_string(),
+            "Write Go.\n\nKeep it short.".to_string(),
+        );
+        fm.variants
+            .insert("claude".to_string(), "Write idiomatic Go.".to_string());
+        let (parsed, unknown, _) = parse_frontmatter_strict(&fm.render("Body.")).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(parsed.variants, fm.variants);
+    }
+
+    #[test]
+    fn frontmatter_errors_point_into_the_file() {
+        let err =
+            parse_frontmatter_strict("\n---\ndescription: x\ntags: [a\n---\nBody.").unwrap_err();
+        assert_eq!(err.line, Some(5));
+        assert_eq!(err.column, Some(1));
+        assert!(
+            err.message.contains("at line 5 column 1"),
+            "{}",
+            err.message
+        );
+        assert!(
+            err.message.contains("at line 4 column 7"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn parse_lifecycle_metadata() {
+        let content = "---\nversion: 2.1\nsince: 2024-05-01\ndeprecated: true\nreplaced_by: go-modern\n---\nBody.";
+        let frag = Fragment::parse(content, "go", Category::Skill, Source::BuiltIn);
+        assert_eq!(frag.version.as_deref(), Some("2.1"));
+        assert_eq!(frag.since.as_deref(), Some("2024-05-01"));
+        assert!(frag.deprecated);
+        assert_eq!(frag.replaced_by.as_deref(), Some("go-modern"));
+
+        let (fm, unknown, _) = parse_frontmatter_strict(content).unwrap();
+        assert!(unknown.is_empty());
+        let (roundtrip, _, _) = parse_frontmatter_strict(&fm.render("Body.")).unwrap();
+        assert_eq!(roundtrip.version.as_deref(), Some("2.1"));
+        assert!(roundtrip.deprecated);
+    }
+
+    #[test]
+    fn localized_falls_back_by_language() {
+        let mut go = Fragment::parse("Go.", "go", Category::Skill, Source::BuiltIn);
+        for (locale, body) in [("ja", "Go（日本語）"), ("pt-BR", "Go em português")] {
+            let mut t = Fragment::parse(body, "go", Category::Skill, Source::BuiltIn);
+            t.locale = Some(locale.to_string());
+            go.translations.insert(locale.to_string(), t);
+        }
+        assert_eq!(go.localized(None).body, "Go.");
+        assert_eq!(go.localized(Some("ja")).body, "Go（日本語）");
+        assert_eq!(go.localized(Some("ja-JP")).body, "Go（日本語）");
+        assert_eq!(go.localized(Some("pt")).body, "Go em português");
+        assert_eq!(go.localized(Some("pt-br")).body, "Go em português");
+        assert_eq!(go.localized(Some("de")).body, "Go.");
+    }
+
+    #[test]
+    fn locale_tags() {
+        for tag in ["ja", "pt-BR", "zh-Hant", "es-419"] {
+            assert!(is_locale_tag(tag), "{tag}");
+        }
+        for tag in [
+            "small",
+            "gpt",
+            "claude-haiku",
+            "JA",
+            "ja-",
+            "js",
+            "qq",
+            "ja-JPN",
+        ] {
+            assert!(!is_locale_tag(tag), "{tag}");
+        }
+    }
+
+    #[test]
+    fn target_suffixes() {
+        let targets = vec!["small".to_string(), "claude".to_string()];
+        assert!(is_target_suffix("small", &targets));
+        assert!(is_target_suffix("claude-haiku", &targets));
+        assert!(!is_target_suffix("claudette", &targets));
+        assert!(!is_target_suffix("js", &targets));
+    }
+
+    #[test]
+    fn body_for_picks_closest_variant() {
+        let content = "---\nvariants:\n  small: Short.\n  claude: Claude.\n  claude-haiku: Haiku.\n---\nDefault.";
+        let frag = Fragment::parse(content, "go", Category::Skill, Source::BuiltIn);
+        assert_eq!(frag.body_for(None), ("Default.", None));
+        assert_eq!(frag.body_for(Some("small")), ("Short.", Some("small")));
+        assert_eq!(
+            frag.body_for(Some("claude-sonnet-4")),
+            ("Claude.", Some("claude"))
+        );
+        assert_eq!(
+            frag.body_for(Some("claude-haiku-4")),
+            ("Haiku.", Some("claude-haiku"))
+        );
+        assert_eq!(frag.body_for(Some("gpt-4o")), ("Default.", None));
+        assert_eq!(frag.body_for(Some("smaller")), ("Default.", None));
+    }
+
     #[test]
     fn from_file_dotted_name() {
         let tmp = tempfile::tempdir().unwrap();
@@ -347,4 +915,34 @@ You are a senior backend engineer."#;
         let frag = Fragment::from_file(&path, Category::Context, Source::Global).unwrap();
         assert_eq!(frag.name, "my.dotted.name");
     }
+
+    #[test]
+    fn define_custom_category_round_trips() {
+        let cat = Category::define("glossary", "glossaries", "Glossary").unwrap();
+        assert!(!cat.is_builtin());
+        assert_eq!(cat.to_string(), "glossary");
+        assert_eq!(cat.dir_name(), "glossaries");
+        let categories = [Category::Skill, cat.clone()];
+        assert_eq!(Category::lookup("glossary", &categories), Some(cat.clone()));
+        assert_eq!(
+            Category::lookup("glossaries", &categories),
+            Some(cat.clone())
+        );
+        assert_eq!(
+            Category::lookup("skills", &categories),
+            Some(Category::Skill)
+        );
+        assert_eq!(Category::lookup_dir("glossaries", &categories), Some(cat));
+        // Custom categories are only known where they're defined
+        assert_eq!(Category::from_name("glossary"), None);
+        assert_eq!(Category::lookup("glossary", Category::all()), None);
+    }
+
+    #[test]
+    fn define_rejects_builtin_and_invalid_names() {
+        assert!(Category::define("skill", "extra-skills", "Skills").is_err());
+        assert!(Category::define("extras", "tones", "Tones").is_err());
+        assert!(Category::define("Bad Name", "bad", "Bad").is_err());
+        assert!(Category::define("escape", "../escape", "Escape").is_err());
+    }
 }
@@ -1,8 +1,16 @@
+pub mod authoring;
 pub mod cli;
 pub mod compose;
+pub mod config;
+pub mod diff;
+pub mod example;
 pub mod fragment;
 pub mod lint;
+pub mod lock;
+pub mod lsp;
+pub mod preset;
 pub mod registry;
 pub mod resolve;
 pub mod server;
 pub mod version;
+pub mod watch;
@@ -0,0 +1,412 @@
+//! `mimic.lock`: content hashes of the fragments that presets and pinned
+//! compositions resolve to, so a prompt can't change silently when
+//! fragments are edited or mimic's built-ins are upgraded.
+
+use crate::compose::{self, ComposeRequest};
+use crate::config::{parse_fragment_ref, Layout};
+use crate::diff;
+use crate::fragment::{Fragment, Source};
+use crate::registry::Registry;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// File name of the lockfile inside the project's `.mimic/` directory.
+pub const LOCK_FILE: &str = "mimic.lock";
+
+const HEADER: &str = "# Generated by `mimic lock`. Do not edit by hand.\n";
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Compose specs pinned with `mimic lock <spec>`, besides the presets,
+    /// which are always locked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compositions: Vec<String>,
+    /// SHA-256 of the project's layout, which arranges the fragments into
+    /// a prompt. Missing from lockfiles written before layouts were locked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    /// Locked fragments keyed by "category/name".
+    #[serde(default)]
+    pub fragments: BTreeMap<String, LockedFragment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedFragment {
+    pub source: Source,
+    pub sha256: String,
+}
+
+impl Lockfile {
+    /// Reads the lockfile in `project_dir`, or `None` when there isn't one.
+    pub fn load(project_dir: &Path) -> Result<Option<Lockfile>, String> {
+        let path = project_dir.join(LOCK_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_yaml::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<(), String> {
+        let path = project_dir.join(LOCK_FILE);
+        let yaml = serde_yaml::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, format!("{}{}", HEADER, yaml))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Locks every preset plus `compositions`, which may be preset names,
+    /// persona names or compose specs.
+    pub fn generate(registry: &Registry, compositions: &[String]) -> Result<Lockfile, String> {
+        let layout = &registry.config().layout;
+        let mut fragments = BTreeMap::new();
+        let presets = registry.presets().into_iter().map(|p| p.name.as_str());
+        for spec in presets.chain(compositions.iter().map(String::as_str)) {
+            let req = diff::resolve_spec(registry, spec)?;
+            for (key, frag) in resolved_fragments(registry, &req, layout)? {
+                fragments.insert(
+                    key,
+                    LockedFragment {
+                        source: frag.source,
+                        sha256: content_hash(frag),
+                    },
+                );
+            }
+        }
+        Ok(Lockfile {
+            compositions: compositions.to_vec(),
+            layout: Some(layout_hash(registry, layout)?),
+            fragments,
+        })
+    }
+
+    /// Checks that `layout` is the locked one and that every fragment `req`
+    /// resolves to is locked and unchanged.
+    pub fn verify(
+        &self,
+        registry: &Registry,
+        req: &ComposeRequest,
+        layout: &Layout,
+    ) -> Result<(), String> {
+        let mut problems = Vec::new();
+        match &self.layout {
+            None => problems.push("the layout is not locked".to_string()),
+            Some(locked) if *locked != layout_hash(registry, layout)? => {
+                problems.push("the layout changed".to_string())
+            }
+            Some(_) => {}
+        }
+        for (key, frag) in resolved_fragments(registry, req, layout)? {
+            match self.fragments.get(&key) {
+                None => problems.push(format!("{} is not locked", key)),
+                Some(locked) if locked.sha256 != content_hash(frag) => {
+                    problems.push(format!("{} changed", key))
+                }
+                Some(_) => {}
+            }
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Composition doesn't match {}: {}. Review the changes and run `mimic lock` to update it.",
+            LOCK_FILE,
+            problems.join(", ")
+        ))
+    }
+}
+
+/// Verifies `req` against the lockfile of the project `registry` was
+/// loaded from. Used when the server runs with `--locked`.
+pub fn verify_locked(
+    registry: &Registry,
+    req: &ComposeRequest,
+    layout: &Layout,
+) -> Result<(), String> {
+    let lock = registry
+        .layer_dir(Source::Project)
+        .map(Lockfile::load)
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| {
+            format!(
+                "Running with --locked but no .mimic/{} was found; run `mimic lock` first",
+                LOCK_FILE
+            )
+        })?;
+    lock.verify(registry, req, layout)
+}
+
+/// SHA-256 of everything in a fragment that can reach a prompt: its body,
+/// variants and translations. Metadata such as the description is left out.
+pub fn content_hash(frag: &Fragment) -> String {
+    let mut hasher = Sha256::new();
+    hash_fragment(&mut hasher, frag);
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_fragment(hasher: &mut Sha256, frag: &Fragment) {
+    // Length prefixes keep distinct layouts of the same bytes apart
+    let mut field = |s: &str| {
+        hasher.update((s.len() as u64).to_le_bytes());
+        hasher.update(s.as_bytes());
+    };
+    field(&frag.body);
+    for (target, body) in &frag.variants {
+        field(target);
+        field(body);
+    }
+    for (locale, translation) in &frag.translations {
+        hasher.update(locale.as_bytes());
+        hash_fragment(hasher, translation);
+    }
+}
+
+/// SHA-256 of how `layout` arranges a prompt from `registry`'s categories:
+/// the section order, each section's heading, and the preamble and
+/// epilogue references. The fragments themselves are hashed separately.
+pub fn layout_hash(registry: &Registry, layout: &Layout) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    let mut field = |s: &str| {
+        hasher.update((s.len() as u64).to_le_bytes());
+        hasher.update(s.as_bytes());
+    };
+    for category in layout.resolved_order(registry.categories())? {
+        field(&category.to_string());
+        // A section without a heading hashes like an empty one; both render
+        // no heading line
+        field(
+            layout
+                .heading(&category, registry.categories())?
+                .unwrap_or(""),
+        );
+    }
+    field(layout.preamble.as_deref().unwrap_or(""));
+    field(layout.epilogue.as_deref().unwrap_or(""));
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The fragments a composition is built from, keyed by "category/name".
+fn resolved_fragments<'a>(
+    registry: &'a Registry,
+    req: &ComposeRequest,
+    layout: &Layout,
+) -> Result<BTreeMap<String, &'a Fragment>, String> {
+    let composition = compose::compose_with_layout(registry, req, layout)?;
+    composition
+        .spans
+        .iter()
+        .map(|span| {
+            let key = format!("{}/{}", span.category, span.name);
+            let (category, name) = parse_fragment_ref(&key, registry.categories())?;
+            let frag = registry
+                .get(category, name)
+                .ok_or_else(|| format!("Fragment {} not found", key))?;
+            Ok((key, frag))
+        })
+        .collect()
+}
+
+/// Entry point for `mimic lock`. With `check`, reports whether the existing
+/// lockfile is up to date instead of rewriting it.
+pub fn run(project_dir: Option<PathBuf>, specs: Vec<String>, check: bool) -> i32 {
+    let Some(project_dir) = project_dir else {
+        eprintln!("mimic: no .mimic directory found; create one in your project root first");
+        return 2;
+    };
+    let registry = Registry::new(Some(project_dir.clone()));
+    let existing = match Lockfile::load(&project_dir) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("mimic: {}", e);
+            return 2;
+        }
+    };
+
+    // Compositions pinned earlier stay pinned
+    let mut compositions = existing
+        .as_ref()
+        .map(|l| l.compositions.clone())
+        .unwrap_or_default();
+    for spec in specs {
+        if !compositions.contains(&spec) && registry.preset(&spec).is_none() {
+            compositions.push(spec);
+        }
+    }
+
+    let lock = match Lockfile::generate(&registry, &compositions) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("mimic: {}", e);
+            return 2;
+        }
+    };
+
+    if check {
+        let Some(existing) = existing else {
+            eprintln!("mimic: no {} found; run `mimic lock`", LOCK_FILE);
+            return 1;
+        };
+        let mut changes = changed_fragments(&existing, &lock);
+        if existing.layout != lock.layout {
+            changes.insert(0, "~ layout".to_string());
+        }
+        if changes.is_empty() && existing.compositions == lock.compositions {
+            println!("{} is up to date.", LOCK_FILE);
+            return 0;
+        }
+        for change in &changes {
+            println!("  {}", change);
+        }
+        println!(
+            "{} is out of date; review the changes and run `mimic lock`.",
+            LOCK_FILE
+        );
+        return 1;
+    }
+
+    if let Err(e) = lock.save(&project_dir) {
+        eprintln!("mimic: {}", e);
+        return 2;
+    }
+    println!(
+        "Locked {} fragment(s) from {} preset(s) and {} composition(s) in {}",
+        lock.fragments.len(),
+        registry.presets().len(),
+        lock.compositions.len(),
+        project_dir.join(LOCK_FILE).display()
+    );
+    0
+}
+
+/// Human-readable differences between two lockfiles' fragments.
+fn changed_fragments(old: &Lockfile, new: &Lockfile) -> Vec<String> {
+    let keys: BTreeSet<&String> = old.fragments.keys().chain(new.fragments.keys()).collect();
+    keys.into_iter()
+        .filter_map(
+            |key| match (old.fragments.get(key), new.fragments.get(key)) {
+                (Some(_), None) => Some(format!("- {}", key)),
+                (None, Some(_)) => Some(format!("+ {}", key)),
+                (Some(a), Some(b)) if a != b => Some(format!("~ {}", key)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> (tempfile::TempDir, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(".mimic");
+        std::fs::create_dir_all(dir.join("skills")).unwrap();
+        std::fs::create_dir_all(dir.join("presets")).unwrap();
+        std::fs::write(dir.join("skills/payments.md"), "Know the payments domain.").unwrap();
+        std::fs::write(
+            dir.join("presets/payments-reviewer.yaml"),
+            "persona: backend-engineer\nskills: [payments]\n",
+        )
+        .unwrap();
+        (tmp, dir)
+    }
+
+    #[test]
+    fn generate_locks_preset_fragments() {
+        let (_tmp, dir) = project();
+        let registry = Registry::new(Some(dir));
+        let lock = Lockfile::generate(&registry, &["backend-engineer?skills=go".into()]).unwrap();
+        let keys: Vec<_> = lock.fragments.keys().map(String::as_str).collect();
+        assert!(keys.contains(&"persona/backend-engineer"));
+        assert!(keys.contains(&"skill/payments"));
+        assert!(keys.contains(&"skill/go"));
+        assert_eq!(lock.fragments["skill/payments"].source, Source::Project);
+        assert_eq!(lock.fragments["skill/go"].source, Source::BuiltIn);
+    }
+
+    #[test]
+    fn verify_detects_changed_and_unlocked_fragments() {
+        let (_tmp, dir) = project();
+        let registry = Registry::new(Some(dir.clone()));
+        Lockfile::generate(&registry, &[])
+            .unwrap()
+            .save(&dir)
+            .unwrap();
+        let lock = Lockfile::load(&dir).unwrap().unwrap();
+        let req = registry.preset("payments-reviewer").unwrap().to_request();
+        let layout = &registry.config().layout;
+        assert!(lock.verify(&registry, &req, layout).is_ok());
+
+        std::fs::write(dir.join("skills/payments.md"), "Something else.").unwrap();
+        let registry = Registry::new(Some(dir));
+        let err = lock.verify(&registry, &req, layout).unwrap_err();
+        assert!(err.contains("skill/payments changed"), "{err}");
+
+        let (other, _) = compose::parse_spec("backend-engineer?skills=go").unwrap();
+        let err = lock.verify(&registry, &other, layout).unwrap_err();
+        assert!(err.contains("skill/go is not locked"), "{err}");
+    }
+
+    #[test]
+    fn verify_detects_layout_changes() {
+        let (_tmp, dir) = project();
+        let registry = Registry::new(Some(dir.clone()));
+        let lock = Lockfile::generate(&registry, &[]).unwrap();
+        let req = registry.preset("payments-reviewer").unwrap().to_request();
+        assert!(lock
+            .verify(&registry, &req, &registry.config().layout)
+            .is_ok());
+
+        // Per-request overrides and config.yaml changes both count
+        let renamed: Layout = serde_yaml::from_str("headings: {skills: Skills}").unwrap();
+        let err = lock.verify(&registry, &req, &renamed).unwrap_err();
+        assert!(err.contains("the layout changed"), "{err}");
+        std::fs::write(
+            dir.join("config.yaml"),
+            "layout:\n  order: [skills, persona]\n",
+        )
+        .unwrap();
+        let registry = Registry::new(Some(dir));
+        let err = lock
+            .verify(&registry, &req, &registry.config().layout)
+            .unwrap_err();
+        assert!(err.contains("the layout changed"), "{err}");
+
+        let unlocked = Lockfile {
+            layout: None,
+            ..lock
+        };
+        let default = Layout::default();
+        let err = unlocked.verify(&registry, &req, &default).unwrap_err();
+        assert!(err.contains("the layout is not locked"), "{err}");
+    }
+
+    #[test]
+    fn content_hash_covers_variants_but_not_metadata() {
+        let base = Fragment::parse(
+            "---\ndescription: a\n---\nBody.",
+            "x",
+            crate::fragment::Category::Skill,
+            Source::Project,
+        );
+        let described = Fragment::parse(
+            "---\ndescription: b\n---\nBody.",
+            "x",
+            crate::fragment::Category::Skill,
+            Source::Project,
+        );
+        let varied = Fragment::parse(
+            "---\nvariants:\n  small: Short.\n---\nBody.",
+            "x",
+            crate::fragment::Category::Skill,
+            Source::Project,
+        );
+        assert_eq!(content_hash(&base), content_hash(&described));
+        assert_ne!(content_hash(&base), content_hash(&varied));
+    }
+}
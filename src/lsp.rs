@@ -0,0 +1,568 @@
+//! `mimic lsp`: a language server for fragment files. It publishes the
+//! linter's diagnostics as you type, completes frontmatter keys and values
+//! from the registry, and shows the description of the fragment a
+//! `replaced_by` refers to on hover, the only key that names a fragment.
+
+use crate::fragment::{parse_frontmatter_strict, Category, Source, KNOWN_FIELDS};
+use crate::lint::{FileChecker, LintDiagnostic, Severity};
+use crate::registry::Registry;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, Hover, HoverContents, HoverParams, HoverProviderCapability,
+    MarkupContent, MarkupKind, NumberOrString, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Serves LSP over stdin and stdout until the client shuts the server down.
+pub fn run(project_dir: Option<PathBuf>) -> i32 {
+    let (connection, io_threads) = Connection::stdio();
+    let status = match serve(connection, project_dir) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("mimic: lsp: {e}");
+            1
+        }
+    };
+    match io_threads.join() {
+        Ok(()) => status,
+        Err(e) => {
+            eprintln!("mimic: lsp: {e}");
+            1
+        }
+    }
+}
+
+/// Runs the server on `connection`, from the `initialize` handshake to
+/// `shutdown`.
+pub fn serve(connection: Connection, project_dir: Option<PathBuf>) -> Result<(), String> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".into(), " ".into(), ",".into(), "[".into()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    let capabilities = serde_json::to_value(capabilities).map_err(|e| e.to_string())?;
+    connection
+        .initialize(capabilities)
+        .map_err(|e| e.to_string())?;
+
+    let mut server = Server::new(project_dir);
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection
+                    .handle_shutdown(&request)
+                    .map_err(|e| e.to_string())?
+                {
+                    return Ok(());
+                }
+                let response = server.request(request);
+                connection
+                    .sender
+                    .send(response.into())
+                    .map_err(|e| e.to_string())?;
+            }
+            Message::Notification(notification) => {
+                for outgoing in server.notification(notification) {
+                    connection
+                        .sender
+                        .send(outgoing.into())
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Server {
+    project_dir: Option<PathBuf>,
+    registry: Registry,
+    /// Text of the open documents, which may differ from what's on disk.
+    documents: HashMap<Uri, String>,
+    /// What each open file is linted against, kept between edits until a
+    /// file is saved, since a save can change `config.yaml` or the
+    /// fragments other files refer to.
+    checkers: HashMap<PathBuf, FileChecker>,
+}
+
+impl Server {
+    fn new(project_dir: Option<PathBuf>) -> Self {
+        Server {
+            registry: Registry::new(project_dir.clone()),
+            project_dir,
+            documents: HashMap::new(),
+            checkers: HashMap::new(),
+        }
+    }
+
+    fn request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            Completion::METHOD => request
+                .extract::<CompletionParams>(Completion::METHOD)
+                .map_err(|e| e.to_string())
+                .map(|(_, params)| {
+                    let doc = params.text_document_position;
+                    let items = self
+                        .document(&doc.text_document.uri)
+                        .map(|(text, path)| completions(&self.registry, text, path, doc.position));
+                    serde_json::to_value(items.map(CompletionResponse::Array))
+                }),
+            HoverRequest::METHOD => request
+                .extract::<HoverParams>(HoverRequest::METHOD)
+                .map_err(|e| e.to_string())
+                .map(|(_, params)| {
+                    let doc = params.text_document_position_params;
+                    let hover = self
+                        .document(&doc.text_document.uri)
+                        .and_then(|(text, path)| hover(&self.registry, text, path, doc.position));
+                    serde_json::to_value(hover)
+                }),
+            method => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request: {method}"),
+                )
+            }
+        };
+        match result {
+            Ok(Ok(value)) => Response::new_ok(id, value),
+            Ok(Err(e)) => Response::new_err(id, ErrorCode::InternalError as i32, e.to_string()),
+            Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e),
+        }
+    }
+
+    /// Handles a notification from the client and returns the ones to send
+    /// back.
+    fn notification(&mut self, notification: Notification) -> Vec<Notification> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                let doc = params.text_document;
+                self.documents.insert(doc.uri.clone(), doc.text);
+                doc.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) = notification.extract::<lsp_types::DidChangeTextDocumentParams>(
+                    DidChangeTextDocument::METHOD,
+                ) else {
+                    return vec![];
+                };
+                // Full sync: the last change holds the whole text
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return vec![];
+                };
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), change.text);
+                uri
+            }
+            DidSaveTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<lsp_types::DidSaveTextDocumentParams>(DidSaveTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                // New tags, groups and fragments become completions, and
+                // config.yaml may have changed
+                self.registry.reload();
+                self.checkers.clear();
+                params.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<lsp_types::DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                if let Some(path) = file_path(&uri) {
+                    self.checkers.remove(&path);
+                }
+                return vec![publish(uri, vec![])];
+            }
+            _ => return vec![],
+        };
+
+        let Some((_, path)) = self.document(&uri) else {
+            return vec![];
+        };
+        let text = &self.documents[&uri];
+        let checker = match self.checkers.entry(path) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                FileChecker::load(self.project_dir.as_deref(), entry.key()).map(|c| entry.insert(c))
+            }
+        };
+        let diagnostics = match checker {
+            Ok(checker) => checker
+                .check(text)
+                .iter()
+                .map(|d| diagnostic(text, d))
+                .collect(),
+            Err(e) => vec![Diagnostic {
+                range: Range::default(),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("mimic".to_string()),
+                message: format!("config.yaml: {e}"),
+                ..Diagnostic::default()
+            }],
+        };
+        vec![publish(uri, diagnostics)]
+    }
+
+    /// The text of an open markdown file and where it lives on disk.
+    fn document(&self, uri: &Uri) -> Option<(&str, PathBuf)> {
+        let text = self.documents.get(uri)?;
+        let path = file_path(uri)?;
+        if path.extension().is_none_or(|e| e != "md") {
+            return None;
+        }
+        Some((text, path))
+    }
+}
+
+fn publish(uri: Uri, diagnostics: Vec<Diagnostic>) -> Notification {
+    let params = PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version: None,
+    };
+    Notification::new(PublishDiagnostics::METHOD.to_string(), params)
+}
+
+/// The local path of a `file:` URI.
+fn file_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().map(|s| s.as_str()) != Some("file") {
+        return None;
+    }
+    let path = uri.path().as_estr().decode().into_string().ok()?;
+    // Windows drive paths arrive as `/C:/...`
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => &path[1..],
+        _ => &path[..],
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Turns a lint diagnostic into an LSP one, underlining from its column to
+/// the end of the line, or the first line when it has no position.
+fn diagnostic(text: &str, d: &LintDiagnostic) -> Diagnostic {
+    let (line, column) = d.position.map_or((0, 0), |p| (p.line - 1, p.column - 1));
+    let line_text = text.lines().nth(line).unwrap_or("");
+    let start: String = line_text.chars().take(column).collect();
+    let range = Range {
+        start: Position::new(line as u32, utf16_len(&start)),
+        end: Position::new(line as u32, utf16_len(line_text)),
+    };
+    Diagnostic {
+        range,
+        severity: Some(match d.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        code: Some(NumberOrString::String(d.rule.to_string())),
+        source: Some("mimic".to_string()),
+        message: d.message.clone(),
+        ..Diagnostic::default()
+    }
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// Byte offset in `line` of an LSP character offset, which counts UTF-16
+/// code units.
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= character {
+            return i;
+        }
+        units += c.len_utf16() as u32;
+    }
+    line.len()
+}
+
+/// What the cursor is on in a fragment's frontmatter.
+#[derive(Debug, PartialEq)]
+enum Slot<'a> {
+    /// A key being typed at the start of a line.
+    Key { prefix: &'a str },
+    /// The value of `key`, or an item of its list.
+    Value { key: &'a str, prefix: &'a str },
+}
+
+/// Finds what the cursor at `position` is on, if it's in the frontmatter.
+/// A frontmatter block that isn't closed yet runs to the end of the file.
+fn slot(text: &str, position: Position) -> Option<Slot<'_>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let cursor = position.line as usize;
+    let open = lines.iter().position(|l| !l.trim().is_empty())?;
+    if lines[open].trim_end() != "---" || cursor <= open {
+        return None;
+    }
+    if lines[open + 1..cursor]
+        .iter()
+        .any(|l| l.trim_end() == "---")
+    {
+        return None;
+    }
+
+    let line = lines.get(cursor).copied().unwrap_or("");
+    let before = &line[..byte_offset(line, position.character)];
+    let start = before
+        .rfind(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ':'))
+        .map_or(0, |i| i + 1);
+    let prefix = &before[start..];
+
+    if before.trim_start().starts_with('#') {
+        return None;
+    }
+    // List items and continuation lines belong to the last top-level key
+    if before.starts_with(char::is_whitespace) || before.starts_with('-') {
+        let key = lines[open + 1..cursor]
+            .iter()
+            .rev()
+            .find(|l| !l.starts_with(char::is_whitespace) && !l.starts_with('-'))
+            .and_then(|l| l.split_once(':'))
+            .map(|(k, _)| k.trim())?;
+        return Some(Slot::Value { key, prefix });
+    }
+    match before.split_once(':') {
+        Some((key, _)) => Some(Slot::Value {
+            key: key.trim(),
+            prefix,
+        }),
+        None => Some(Slot::Key { prefix }),
+    }
+}
+
+/// The category of the fragment at `path`: its category directory, or the
+/// `category` in the frontmatter of a root-level file.
+fn category_of(registry: &Registry, text: &str, path: &Path) -> Option<Category> {
+    let dir = path.parent()?.file_name()?.to_str()?;
+    Category::lookup_dir(dir, registry.categories()).or_else(|| {
+        let (fm, _, _) = parse_frontmatter_strict(text).ok()?;
+        fm.category.as_deref().and_then(|c| registry.category(c))
+    })
+}
+
+fn completions(
+    registry: &Registry,
+    text: &str,
+    path: PathBuf,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some(slot) = slot(text, position) else {
+        return vec![];
+    };
+    let prefix = match slot {
+        Slot::Key { prefix } | Slot::Value { prefix, .. } => prefix,
+    };
+    let range = Range {
+        start: Position::new(
+            position.line,
+            position.character.saturating_sub(utf16_len(prefix)),
+        ),
+        end: position,
+    };
+    let item = |label: &str, insert: String, kind, detail: Option<String>| CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail,
+        text_edit: Some(lsp_types::CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: insert,
+        })),
+        ..CompletionItem::default()
+    };
+
+    match slot {
+        Slot::Key { prefix } => {
+            let present: Vec<&str> = text
+                .lines()
+                .filter_map(|l| l.split_once(':'))
+                .map(|(k, _)| k)
+                .collect();
+            KNOWN_FIELDS
+                .iter()
+                .filter(|k| k.starts_with(prefix) && !present.contains(k))
+                .map(|k| item(k, format!("{k}: "), CompletionItemKind::PROPERTY, None))
+                .collect()
+        }
+        Slot::Value { key, prefix } => {
+            let values: Vec<(String, Option<String>)> = match key {
+                "tags" => registry
+                    .all_tags()
+                    .iter()
+                    .map(|t| (t.clone(), None))
+                    .collect(),
+                "group" | "skill_groups" => registry
+                    .all_groups()
+                    .iter()
+                    .map(|g| (g.clone(), None))
+                    .collect(),
+                "category" => registry
+                    .categories()
+                    .iter()
+                    .map(|c| (c.to_string(), None))
+                    .collect(),
+                "locale" => registry
+                    .all_locales()
+                    .into_iter()
+                    .map(|l| (l.to_string(), None))
+                    .collect(),
+                "deprecated" => vec![("true".to_string(), None), ("false".to_string(), None)],
+                // A reference to another fragment of the same category
+                "replaced_by" => {
+                    let Some(category) = category_of(registry, text, &path) else {
+                        return vec![];
+                    };
+                    let own = path.file_stem().and_then(|s| s.to_str());
+                    registry
+                        .names_for_category(category.clone())
+                        .iter()
+                        .filter(|n| Some(n.as_str()) != own)
+                        .map(|n| {
+                            let description = registry
+                                .get(category.clone(), n)
+                                .map(|f| f.description.clone());
+                            (n.clone(), description)
+                        })
+                        .collect()
+                }
+                _ => vec![],
+            };
+            let kind = match key {
+                "replaced_by" => CompletionItemKind::REFERENCE,
+                _ => CompletionItemKind::VALUE,
+            };
+            values
+                .into_iter()
+                .filter(|(v, _)| v.starts_with(prefix))
+                .map(|(v, detail)| item(&v, v.clone(), kind, detail))
+                .collect()
+        }
+    }
+}
+
+/// The description of the fragment named by the `replaced_by` value under
+/// the cursor.
+fn hover(registry: &Registry, text: &str, path: PathBuf, position: Position) -> Option<Hover> {
+    let line = text.lines().nth(position.line as usize)?;
+    let (key, value) = line.split_once(':')?;
+    if key.trim() != "replaced_by" || byte_offset(line, position.character) <= key.len() {
+        return None;
+    }
+    let name = value.split('#').next()?.trim().trim_matches(['"', '\'']);
+    let category = category_of(registry, text, &path)?;
+    let fragment = registry.get(category.clone(), name)?;
+    let source = match fragment.source {
+        Source::BuiltIn => "built-in",
+        Source::Global => "global",
+        Source::Project => "project",
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!(
+                "**{}** ({}, {})\n\n{}",
+                fragment.name, category, source, fragment.description
+            ),
+        }),
+        range: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str =
+        "---\ndescription: Go\ntags: [go, back\ngroup: \nskill_groups:\n  - ba\n---\nBody text\n";
+
+    fn at(line: u32, character: u32) -> Position {
+        Position::new(line, character)
+    }
+
+    #[test]
+    fn slot_finds_keys_and_values_in_the_frontmatter() {
+        assert_eq!(slot(DOC, at(0, 0)), None);
+        assert_eq!(slot(DOC, at(1, 3)), Some(Slot::Key { prefix: "des" }));
+        assert_eq!(
+            slot(DOC, at(2, 16)),
+            Some(Slot::Value {
+                key: "tags",
+                prefix: "back"
+            })
+        );
+        assert_eq!(
+            slot(DOC, at(3, 7)),
+            Some(Slot::Value {
+                key: "group",
+                prefix: ""
+            })
+        );
+        assert_eq!(
+            slot(DOC, at(5, 6)),
+            Some(Slot::Value {
+                key: "skill_groups",
+                prefix: "ba"
+            })
+        );
+        assert_eq!(slot(DOC, at(7, 4)), None);
+    }
+
+    #[test]
+    fn slot_runs_to_the_end_of_an_unclosed_frontmatter() {
+        let text = "---\ndescription: Draft\nta";
+        assert_eq!(slot(text, at(2, 2)), Some(Slot::Key { prefix: "ta" }));
+        assert_eq!(slot("No frontmatter\nta", at(1, 2)), None);
+    }
+
+    #[test]
+    fn diagnostics_use_utf16_columns() {
+        let d = LintDiagnostic {
+            severity: Severity::Warning,
+            file_path: "x.md".to_string(),
+            rule: "test",
+            message: "m".to_string(),
+            position: Some(crate::lint::Position { line: 2, column: 3 }),
+            source_line: None,
+        };
+        let converted = diagnostic("---\n😀😀x\n", &d);
+        assert_eq!(converted.range.start, at(1, 4));
+        assert_eq!(converted.range.end, at(1, 5));
+        assert_eq!(converted.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(byte_offset("😀😀x", 4), 8);
+    }
+
+    #[test]
+    fn file_uris_become_paths() {
+        let uri: Uri = "file:///tmp/my%20skills/go.md".parse().unwrap();
+        assert_eq!(file_path(&uri), Some(PathBuf::from("/tmp/my skills/go.md")));
+        let untitled: Uri = "untitled:Untitled-1".parse().unwrap();
+        assert_eq!(file_path(&untitled), None);
+    }
+}
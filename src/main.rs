@@ -1,9 +1,13 @@
 use clap::Parser;
 use mimic_ai::cli::{Cli, Commands};
+use mimic_ai::diff;
 use mimic_ai::lint;
-use mimic_ai::registry::Registry;
+use mimic_ai::lock;
+use mimic_ai::lsp;
+use mimic_ai::registry::{self, Registry};
 use mimic_ai::server::MimicServer;
-use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use mimic_ai::watch;
+use notify::RecommendedWatcher;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
@@ -13,23 +17,74 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Lint { warnings }) => {
-            std::process::exit(lint::run(warnings));
+        Some(Commands::Lint {
+            warnings,
+            format,
+            fix,
+            dry_run,
+            rules,
+            no_builtin,
+            watch,
+            paths,
+        }) => {
+            let fix = match (fix, dry_run) {
+                (false, _) => None,
+                (true, false) => Some(lint::FixMode::Apply),
+                (true, true) => Some(lint::FixMode::DryRun),
+            };
+            let opts = lint::LintOptions {
+                show_warnings: warnings,
+                format,
+                fix,
+                rules,
+                paths,
+                no_builtin,
+            };
+            if watch {
+                std::process::exit(lint::watch(find_project_dir(), &opts));
+            }
+            std::process::exit(lint::run(find_project_dir(), &opts));
+        }
+        Some(Commands::Diff { left, right }) => {
+            std::process::exit(diff::run(find_project_dir(), &left, &right));
+        }
+        Some(Commands::Lock { specs, check }) => {
+            std::process::exit(lock::run(find_project_dir(), specs, check));
+        }
+        Some(Commands::Lsp) => {
+            std::process::exit(lsp::run(find_project_dir()));
         }
         None => {
-            run_server().await?;
+            run_server(cli.locked).await?;
         }
     }
 
     Ok(())
 }
 
-async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
+async fn run_server(locked: bool) -> Result<(), Box<dyn std::error::Error>> {
     let project_dir = find_project_dir();
     let registry = Arc::new(RwLock::new(Registry::new(project_dir.clone())));
 
-    let watcher = setup_watcher(Arc::clone(&registry), project_dir);
-    let handler = MimicServer::new(registry, watcher);
+    let watcher = setup_watcher(Arc::clone(&registry), project_dir.clone());
+    let mut handler = MimicServer::new(registry, watcher).locked(locked);
+
+    // Nested `.mimic` directories (e.g. one per service in a monorepo) each
+    // get their own registry, selectable through the `project` parameter.
+    // Without a project or a git checkout there's no workspace to search:
+    // clients often start servers from `$HOME` or `/`.
+    let workspace_root = project_dir
+        .as_deref()
+        .and_then(|d| d.parent())
+        .map(|d| d.to_path_buf())
+        .or_else(|| find_git_root_from(&std::env::current_dir().ok()?));
+    if let Some(root) = workspace_root {
+        for (name, dir) in registry::discover_projects(&root) {
+            let project_registry = Arc::new(RwLock::new(Registry::new(Some(dir.clone()))));
+            let project_watcher = setup_watcher(Arc::clone(&project_registry), Some(dir));
+            handler = handler.with_project(name, project_registry, project_watcher);
+        }
+    }
 
     let (stdin, stdout) = rmcp::transport::stdio();
     let service = rmcp::serve_server(handler, (stdin, stdout)).await?;
@@ -51,42 +106,22 @@ fn setup_watcher(
         return None;
     }
 
-    let last_rebuild = Arc::new(std::sync::Mutex::new(Instant::now()));
+    let mut last_rebuild = Instant::now();
     let rebuild_project_dir = project_dir;
 
-    let mut watcher = match notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
-        if res.is_err() {
-            return;
-        }
-
+    watch::watch_dirs(&watched_dirs, move |_| {
         // Debounce: ignore events within 500ms of last rebuild
-        let mut last = last_rebuild.lock().unwrap();
-        if last.elapsed().as_millis() < 500 {
+        if last_rebuild.elapsed().as_millis() < 500 {
             return;
         }
-        *last = Instant::now();
-        drop(last);
+        last_rebuild = Instant::now();
 
         let new_registry = Registry::new(rebuild_project_dir.clone());
         if let Ok(mut reg) = registry.write() {
             *reg = new_registry;
         }
         eprintln!("mimic: registry reloaded");
-    }) {
-        Ok(w) => w,
-        Err(e) => {
-            eprintln!("mimic: warning: failed to set up file watcher: {}", e);
-            return None;
-        }
-    };
-
-    for dir in &watched_dirs {
-        if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
-            eprintln!("mimic: warning: failed to watch {}: {}", dir.display(), e);
-        }
-    }
-
-    Some(watcher)
+    })
 }
 
 fn find_project_dir() -> Option<PathBuf> {
@@ -105,6 +140,14 @@ fn find_project_dir_from(start: &std::path::Path) -> Option<PathBuf> {
     }
 }
 
+/// The nearest directory at or above `start` that holds a `.git`.
+fn find_git_root_from(start: &std::path::Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(|dir| dir.to_path_buf())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,6 +181,18 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn find_git_root_in_parent() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let child = tmp.path().join("services").join("api");
+        std::fs::create_dir_all(&child).unwrap();
+        assert_eq!(find_git_root_from(&child), Some(tmp.path().to_path_buf()));
+
+        let outside = tempfile::tempdir().unwrap();
+        assert_eq!(find_git_root_from(outside.path()), None);
+    }
+
     #[test]
     fn find_project_dir_file_not_dir() {
         let tmp = tempfile::tempdir().unwrap();
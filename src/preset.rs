@@ -0,0 +1,136 @@
+use crate::compose::ComposeRequest;
+use crate::fragment::Source;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A named, reusable composition stored as `presets/{name}.yaml` in the
+/// global or project layer.
+#[derive(Debug, Clone, Serialize)]
+pub struct Preset {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub persona: String,
+    pub skills: Vec<String>,
+    pub contexts: Vec<String>,
+    pub tones: Vec<String>,
+    pub constraints: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    /// Model target used to pick fragment variants.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Language to compose in, for fragments that have a translation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Swap deprecated fragments for their `replaced_by` fragment.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replace_deprecated: bool,
+    /// Fragments for custom categories, keyed by category name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, Vec<String>>,
+    pub source: Source,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetFile {
+    #[serde(default)]
+    description: Option<String>,
+    persona: String,
+    #[serde(default)]
+    skills: Vec<String>,
+    #[serde(default)]
+    contexts: Vec<String>,
+    #[serde(default)]
+    tones: Vec<String>,
+    #[serde(default)]
+    constraints: Vec<String>,
+    #[serde(default)]
+    examples: Vec<String>,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    locale: Option<String>,
+    #[serde(default)]
+    replace_deprecated: bool,
+    #[serde(default)]
+    custom: BTreeMap<String, Vec<String>>,
+}
+
+impl Preset {
+    pub fn parse(content: &str, name: &str, source: Source) -> Result<Preset, String> {
+        let file: PresetFile = serde_yaml::from_str(content).map_err(|e| format!("{e}"))?;
+        Ok(Preset {
+            name: name.to_string(),
+            description: file.description,
+            persona: file.persona,
+            skills: file.skills,
+            contexts: file.contexts,
+            tones: file.tones,
+            constraints: file.constraints,
+            examples: file.examples,
+            target: file.target,
+            locale: file.locale,
+            replace_deprecated: file.replace_deprecated,
+            custom: file.custom,
+            source,
+        })
+    }
+
+    pub fn from_file(path: &Path, source: Source) -> Result<Preset, String> {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| format!("invalid preset file name: {}", path.display()))?;
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{e}"))?;
+        Preset::parse(&content, name, source)
+    }
+
+    pub fn to_request(&self) -> ComposeRequest {
+        ComposeRequest {
+            persona: self.persona.clone(),
+            skills: self.skills.clone(),
+            contexts: self.contexts.clone(),
+            tones: self.tones.clone(),
+            constraints: self.constraints.clone(),
+            examples: self.examples.clone(),
+            target: self.target.clone(),
+            locale: self.locale.clone(),
+            replace_deprecated: self.replace_deprecated,
+            custom: self.custom.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_full_preset() {
+        let content = "description: Go API reviews\npersona: backend-engineer\nskills: [go, postgresql]\ncontexts: [code-review]\ntones: [concise]\n";
+        let preset = Preset::parse(content, "go-review", Source::Project).unwrap();
+        assert_eq!(preset.name, "go-review");
+        assert_eq!(preset.description.as_deref(), Some("Go API reviews"));
+        let req = preset.to_request();
+        assert_eq!(req.persona, "backend-engineer");
+        assert_eq!(req.skills, vec!["go", "postgresql"]);
+        assert_eq!(req.contexts, vec!["code-review"]);
+        assert_eq!(req.tones, vec!["concise"]);
+        assert!(req.constraints.is_empty());
+    }
+
+    #[test]
+    fn parse_requires_persona() {
+        assert!(Preset::parse("skills: [go]\n", "x", Source::Global).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_fields() {
+        let err =
+            Preset::parse("persona: designer\nskill: [css]\n", "x", Source::Global).unwrap_err();
+        assert!(err.contains("skill"), "{err}");
+    }
+}
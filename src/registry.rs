@@ -1,12 +1,18 @@
-use crate::fragment::{Category, Fragment, Frontmatter, Source};
+use crate::config::Config;
+use crate::fragment::{is_locale_tag, is_target_suffix, Category, Fragment, Frontmatter, Source};
+use crate::preset::Preset;
 use include_dir::{include_dir, Dir};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
 static DEFAULTS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/defaults");
 
 pub struct Registry {
     fragments: HashMap<Category, HashMap<String, Fragment>>,
+    presets: BTreeMap<String, Preset>,
+    config: Config,
+    /// Built-in and custom categories in default section order.
+    categories: Vec<Category>,
     // Pre-computed indexes
     cached_tags: Vec<String>,
     cached_groups: Vec<String>,
@@ -24,30 +30,42 @@ impl Registry {
         // Built-in defaults (lowest priority)
         load_builtin(&mut fragments);
 
-        let global_dir = global_config_dir();
+        let layers = layer_dirs(project_dir.as_deref());
+        let global_dir = layers
+            .iter()
+            .find(|(source, _)| *source == Source::Global)
+            .map(|(_, dir)| dir.clone());
 
         // Ensure directories exist on first run
-        if let Some(ref global_dir) = global_dir {
-            ensure_dir_exists(global_dir);
-        }
-        if let Some(ref proj) = project_dir {
-            ensure_dir_exists(proj);
+        for (_, dir) in &layers {
+            ensure_dir_exists(dir);
         }
 
-        // Global config
-        if let Some(ref global_dir) = global_dir {
-            load_from_fs(&mut fragments, global_dir, Source::Global);
-        }
+        // Config comes first: it may declare custom categories whose
+        // directories the fragment loaders need to know about.
+        let config = Config::load(layers.iter().map(|(_, d)| d.as_path()));
+        let categories = config.resolve_categories().unwrap_or_else(|e| {
+            eprintln!("mimic: warning: ignoring custom categories: {}", e);
+            Category::all().to_vec()
+        });
+
+        let mut presets = BTreeMap::new();
 
-        // Project-local (highest priority)
-        if let Some(ref proj) = project_dir {
-            load_from_fs(&mut fragments, proj, Source::Project);
+        // Global, then project-local (highest priority)
+        for (source, dir) in &layers {
+            load_from_fs(&mut fragments, dir, *source, &categories);
+            load_presets(&mut presets, dir, *source);
         }
 
-        let (cached_tags, cached_groups, cached_names) = build_indexes(&fragments);
+        fold_siblings(&mut fragments, &config.variant_targets());
+
+        let (cached_tags, cached_groups, cached_names) = build_indexes(&fragments, &categories);
 
         Registry {
             fragments,
+            presets,
+            config,
+            categories,
             cached_tags,
             cached_groups,
             cached_names,
@@ -89,6 +107,31 @@ impl Registry {
         results
     }
 
+    pub fn preset(&self, name: &str) -> Option<&Preset> {
+        self.presets.get(name)
+    }
+
+    /// All presets, sorted by name.
+    pub fn presets(&self) -> Vec<&Preset> {
+        self.presets.values().collect()
+    }
+
+    /// Built-in and custom categories in default section order.
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    /// Finds a category of this registry by name or directory, singular or
+    /// plural for the built-ins.
+    pub fn category(&self, name: &str) -> Option<Category> {
+        Category::lookup(name, &self.categories)
+    }
+
+    /// Settings from `config.yaml`, project layered over global.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     pub fn all_groups(&self) -> &[String] {
         &self.cached_groups
     }
@@ -97,11 +140,50 @@ impl Registry {
         &self.cached_tags
     }
 
+    /// Every target that some fragment has a variant for, sorted.
+    pub fn all_targets(&self) -> Vec<&str> {
+        let targets: BTreeSet<&str> = self
+            .fragments
+            .values()
+            .flat_map(|m| m.values())
+            .flat_map(|f| std::iter::once(f).chain(f.translations.values()))
+            .flat_map(|f| f.variants.keys().map(String::as_str))
+            .collect();
+        targets.into_iter().collect()
+    }
+
+    /// Every locale that some fragment has a translation for, sorted.
+    pub fn all_locales(&self) -> Vec<&str> {
+        let locales: BTreeSet<&str> = self
+            .fragments
+            .values()
+            .flat_map(|m| m.values())
+            .flat_map(|f| f.translations.keys().map(String::as_str))
+            .collect();
+        locales.into_iter().collect()
+    }
+
     pub fn names_for_category(&self, category: Category) -> &[String] {
         static EMPTY: Vec<String> = Vec::new();
         self.cached_names.get(&category).unwrap_or(&EMPTY)
     }
 
+    /// Returns the on-disk directory backing a writable layer, if any.
+    /// Built-ins are compiled into the binary and have no directory.
+    pub fn layer_dir(&self, source: Source) -> Option<&Path> {
+        match source {
+            Source::BuiltIn => None,
+            Source::Global => self.global_dir.as_deref(),
+            Source::Project => self.project_dir.as_deref(),
+        }
+    }
+
+    /// Re-reads every layer from disk, picking up changes immediately rather
+    /// than waiting for the file watcher.
+    pub fn reload(&mut self) {
+        *self = Registry::new(self.project_dir.clone());
+    }
+
     /// Returns the directories that should be watched for hot reload.
     pub fn watched_dirs(&self) -> Vec<PathBuf> {
         let mut dirs = Vec::new();
@@ -115,8 +197,64 @@ impl Registry {
     }
 }
 
+/// Directories never descended into when discovering nested projects.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+const MAX_DISCOVERY_DEPTH: usize = 4;
+
+/// Finds nested `.mimic` directories below `root` (e.g. one per service in a
+/// monorepo). Returns `(project_name, mimic_dir)` pairs sorted by name, where
+/// the name is the path of the owning directory relative to `root`. The
+/// `.mimic` directory directly inside `root` is not included.
+pub fn discover_projects(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut projects = Vec::new();
+    discover_projects_in(root, root, 0, &mut projects);
+    projects.sort();
+    projects
+}
+
+fn discover_projects_in(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    projects: &mut Vec<(String, PathBuf)>,
+) {
+    if depth > MAX_DISCOVERY_DEPTH {
+        return;
+    }
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n,
+            None => continue,
+        };
+        if name.starts_with('.') || SKIPPED_DIRS.contains(&name) {
+            continue;
+        }
+        let candidate = path.join(".mimic");
+        if candidate.is_dir() {
+            if let Ok(rel) = path.strip_prefix(root) {
+                let project_name = rel
+                    .components()
+                    .filter_map(|c| c.as_os_str().to_str())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                projects.push((project_name, candidate));
+            }
+        }
+        discover_projects_in(root, &path, depth + 1, projects);
+    }
+}
+
 fn build_indexes(
     fragments: &HashMap<Category, HashMap<String, Fragment>>,
+    categories: &[Category],
 ) -> (Vec<String>, Vec<String>, HashMap<Category, Vec<String>>) {
     let mut tags: BTreeSet<String> = BTreeSet::new();
     let mut groups: BTreeSet<String> = BTreeSet::new();
@@ -133,11 +271,11 @@ fn build_indexes(
     }
 
     let mut names: HashMap<Category, Vec<String>> = HashMap::new();
-    for cat in Category::all() {
+    for cat in categories {
         if let Some(map) = fragments.get(cat) {
             let mut cat_names: Vec<String> = map.keys().cloned().collect();
             cat_names.sort();
-            names.insert(*cat, cat_names);
+            names.insert(cat.clone(), cat_names);
         }
     }
 
@@ -152,6 +290,18 @@ fn global_config_dir() -> Option<PathBuf> {
     dirs::home_dir().map(|d| d.join(".mimic"))
 }
 
+/// The on-disk fragment layers, lowest priority first: the global
+/// `~/.mimic` directory, then the project's `.mimic` when there is one.
+/// `mimic lint` walks the same list so it checks what the server loads.
+/// The directories may not exist yet.
+pub fn layer_dirs(project_dir: Option<&Path>) -> Vec<(Source, PathBuf)> {
+    global_config_dir()
+        .map(|d| (Source::Global, d))
+        .into_iter()
+        .chain(project_dir.map(|d| (Source::Project, d.to_path_buf())))
+        .collect()
+}
+
 fn ensure_dir_exists(base: &Path) {
     for category in Category::all() {
         let dir = base.join(category.dir_name());
@@ -165,8 +315,9 @@ fn load_from_fs(
     fragments: &mut HashMap<Category, HashMap<String, Fragment>>,
     base: &Path,
     source: Source,
+    categories: &[Category],
 ) {
-    for category in Category::all() {
+    for category in categories {
         let dir = base.join(category.dir_name());
         if !dir.is_dir() {
             continue;
@@ -184,13 +335,10 @@ fn load_from_fs(
             if path.extension().and_then(|e| e.to_str()) != Some("md") {
                 continue;
             }
-            if let Some(frag) = Fragment::from_file(&path, *category, source) {
+            if let Some(frag) = Fragment::from_file(&path, category.clone(), source) {
                 // First-match-wins: higher priority sources are loaded last,
                 // so they override.
-                fragments
-                    .entry(*category)
-                    .or_default()
-                    .insert(frag.name.clone(), frag);
+                insert_overriding(fragments, frag);
             }
         }
     }
@@ -223,7 +371,7 @@ fn load_from_fs(
                 continue;
             }
         };
-        let category = match Category::from_name(category_str) {
+        let category = match Category::lookup(category_str, categories) {
             Some(c) => c,
             None => {
                 eprintln!(
@@ -235,10 +383,102 @@ fn load_from_fs(
             }
         };
         if let Some(frag) = Fragment::from_file(&path, category, source) {
-            fragments
-                .entry(category)
-                .or_default()
-                .insert(frag.name.clone(), frag);
+            insert_overriding(fragments, frag);
+        }
+    }
+}
+
+/// Loads `presets/*.yaml` from a layer. Later layers override earlier ones.
+fn load_presets(presets: &mut BTreeMap<String, Preset>, base: &Path, source: Source) {
+    let dir = base.join("presets");
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            continue;
+        }
+        match Preset::from_file(&path, source) {
+            Ok(preset) => {
+                presets.insert(preset.name.clone(), preset);
+            }
+            Err(e) => {
+                eprintln!("mimic: warning: skipping preset {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Inserts `frag`, replacing any fragment of the same name and recording the
+/// layers it now shadows.
+fn insert_overriding(
+    fragments: &mut HashMap<Category, HashMap<String, Fragment>>,
+    mut frag: Fragment,
+) {
+    let map = fragments.entry(frag.category.clone()).or_default();
+    if let Some(previous) = map.remove(&frag.name) {
+        frag.shadows = previous.shadows;
+        if previous.source != frag.source && !frag.shadows.contains(&previous.source) {
+            frag.shadows.push(previous.source);
+        }
+    }
+    map.insert(frag.name.clone(), frag);
+}
+
+/// Folds sibling files into their base fragment: `go.ja.md` (a locale
+/// suffix, or any sibling with `locale:` frontmatter) becomes the Japanese
+/// translation of `go`, and `go.small.md` its `small` variant. A dotted name
+/// only counts as a sibling when the base fragment exists and comes from the
+/// same or a lower layer, and when its suffix is a locale, one of `targets`,
+/// or the file sets `locale:`; otherwise it stays a fragment in its own right,
+/// so `node.js` stays apart from `node`. Variants declared in frontmatter take
+/// precedence.
+fn fold_siblings(fragments: &mut HashMap<Category, HashMap<String, Fragment>>, targets: &[String]) {
+    for map in fragments.values_mut() {
+        let mut siblings: Vec<String> = map
+            .keys()
+            .filter(|name| {
+                let sibling = &map[name.as_str()];
+                name.rsplit_once('.').is_some_and(|(base, suffix)| {
+                    (sibling.locale.is_some()
+                        || is_locale_tag(suffix)
+                        || is_target_suffix(suffix, targets))
+                        && map.get(base).is_some_and(|b| b.source <= sibling.source)
+                })
+            })
+            .cloned()
+            .collect();
+        // Innermost first, so `go.ja.small` joins `go.ja` before `go.ja` joins `go`
+        siblings.sort_by_key(|name| std::cmp::Reverse(name.matches('.').count()));
+        for name in siblings {
+            let Some(mut sibling) = map.remove(&name) else {
+                continue;
+            };
+            let (base, suffix) = name.rsplit_once('.').unwrap_or_default();
+            let Some(frag) = map.get_mut(base) else {
+                continue;
+            };
+            let locale = sibling
+                .locale
+                .clone()
+                .or_else(|| is_locale_tag(suffix).then(|| suffix.to_string()));
+            match locale {
+                Some(locale) => {
+                    sibling.name = base.to_string();
+                    sibling.locale = Some(locale.clone());
+                    frag.translations.entry(locale).or_insert(sibling);
+                }
+                None => {
+                    frag.variants
+                        .entry(suffix.to_string())
+                        .or_insert(sibling.body);
+                }
+            }
         }
     }
 }
@@ -274,10 +514,10 @@ fn load_builtin(fragments: &mut HashMap<Category, HashMap<String, Fragment>>) {
                     Some(c) => c,
                     None => continue,
                 };
-                let frag = Fragment::parse(content, name, *category, Source::BuiltIn);
+                let frag = Fragment::parse(content, name, category.clone(), Source::BuiltIn);
                 // Don't override — built-in is lowest priority
                 fragments
-                    .entry(*category)
+                    .entry(category.clone())
                     .or_default()
                     .entry(frag.name.clone())
                     .or_insert(frag);
@@ -451,6 +691,86 @@ mod tests {
         let frag = registry.get(Category::Persona, "backend-engineer").unwrap();
         assert_eq!(frag.description, "Custom backend engineer");
         assert_eq!(frag.source, Source::Project);
+        assert!(frag.shadows.contains(&Source::BuiltIn));
+    }
+
+    #[test]
+    fn sibling_files_become_variants() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills_dir = tmp.path().join("skills");
+        std::fs::create_dir_all(&skills_dir).unwrap();
+        // A variant of a built-in, a dotted name with no base, and a
+        // project fragment with its own variant
+        std::fs::write(skills_dir.join("go.small.md"), "Write Go.").unwrap();
+        std::fs::write(skills_dir.join("v1.2.md"), "Version notes.").unwrap();
+        std::fs::write(skills_dir.join("house.md"), "House rules.").unwrap();
+        std::fs::write(skills_dir.join("house.small.md"), "Rules.").unwrap();
+        // Undeclared suffixes stay separate fragments
+        std::fs::write(skills_dir.join("house.js.md"), "House JS rules.").unwrap();
+        std::fs::write(skills_dir.join("house.claude.md"), "Claude rules.").unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let go = registry.get(Category::Skill, "go").unwrap();
+        assert_eq!(go.source, Source::BuiltIn);
+        assert_eq!(go.variants["small"], "Write Go.");
+        assert!(registry.get(Category::Skill, "go.small").is_none());
+        assert!(registry.get(Category::Skill, "v1.2").is_some());
+        let house = registry.get(Category::Skill, "house").unwrap();
+        assert_eq!(house.body_for(Some("small")).0, "Rules.");
+        assert!(registry.all_targets().contains(&"small"));
+        assert!(registry.get(Category::Skill, "house.js").is_some());
+        assert!(registry.get(Category::Skill, "house.claude").is_some());
+
+        std::fs::write(tmp.path().join("config.yaml"), "targets: [claude]\n").unwrap();
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let house = registry.get(Category::Skill, "house").unwrap();
+        assert_eq!(house.body_for(Some("claude-haiku")).0, "Claude rules.");
+        assert!(registry.get(Category::Skill, "house.js").is_some());
+    }
+
+    #[test]
+    fn sibling_files_become_translations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills_dir = tmp.path().join("skills");
+        std::fs::create_dir_all(&skills_dir).unwrap();
+        std::fs::write(
+            skills_dir.join("go.ja.md"),
+            "---\ndescription: Go の専門知識\n---\n慣用的な Go を書きます。",
+        )
+        .unwrap();
+        std::fs::write(skills_dir.join("go.ja.small.md"), "Go を書く。").unwrap();
+        std::fs::write(
+            skills_dir.join("go.brazil.md"),
+            "---\nlocale: pt-BR\n---\nVocê escreve Go idiomático.",
+        )
+        .unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let go = registry.get(Category::Skill, "go").unwrap();
+        assert_eq!(
+            go.translations.keys().collect::<Vec<_>>(),
+            vec!["ja", "pt-BR"]
+        );
+        let ja = go.localized(Some("ja"));
+        assert_eq!(ja.description, "Go の専門知識");
+        assert_eq!(ja.body_for(Some("small")).0, "Go を書く。");
+        assert!(go.variants.is_empty());
+        assert_eq!(registry.all_locales(), vec!["ja", "pt-BR"]);
+        assert!(registry.get(Category::Skill, "go.brazil").is_none());
+    }
+
+    #[test]
+    fn lower_layer_variant_does_not_attach_to_override() {
+        let mut fragments: HashMap<Category, HashMap<String, Fragment>> = HashMap::new();
+        let parse = |content: &str, name: &str, source: Source| {
+            Fragment::parse(content, name, Category::Skill, source)
+        };
+        insert_overriding(&mut fragments, parse("Short.", "go.small", Source::BuiltIn));
+        insert_overriding(&mut fragments, parse("Project Go.", "go", Source::Project));
+        fold_siblings(&mut fragments, &["small".to_string()]);
+        let skills = &fragments[&Category::Skill];
+        assert!(skills["go"].variants.is_empty());
+        assert!(skills.contains_key("go.small"));
     }
 
     #[test]
@@ -463,7 +783,7 @@ mod tests {
         .unwrap();
 
         let mut fragments: HashMap<Category, HashMap<String, Fragment>> = HashMap::new();
-        load_from_fs(&mut fragments, tmp.path(), Source::Project);
+        load_from_fs(&mut fragments, tmp.path(), Source::Project, Category::all());
 
         let frag = fragments
             .get(&Category::Skill)
@@ -484,7 +804,7 @@ mod tests {
         .unwrap();
 
         let mut fragments: HashMap<Category, HashMap<String, Fragment>> = HashMap::new();
-        load_from_fs(&mut fragments, tmp.path(), Source::Project);
+        load_from_fs(&mut fragments, tmp.path(), Source::Project, Category::all());
 
         // Should not appear in any category
         for cat in Category::all() {
@@ -510,6 +830,48 @@ mod tests {
         assert_eq!(frag.source, Source::Project);
     }
 
+    #[test]
+    fn discover_projects_finds_nested_mimic_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".mimic")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("services/backend/.mimic")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("mobile/.mimic")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("node_modules/pkg/.mimic")).unwrap();
+
+        let projects = discover_projects(tmp.path());
+        let names: Vec<&str> = projects.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["mobile", "services/backend"]);
+        assert_eq!(projects[1].1, tmp.path().join("services/backend/.mimic"));
+    }
+
+    #[test]
+    fn discover_projects_empty_when_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src/deep")).unwrap();
+        assert!(discover_projects(tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn project_presets_load() {
+        let tmp = tempfile::tempdir().unwrap();
+        let presets_dir = tmp.path().join("presets");
+        std::fs::create_dir_all(&presets_dir).unwrap();
+        std::fs::write(
+            presets_dir.join("go-review.yaml"),
+            "persona: backend-engineer\nskills: [go]\ncontexts: [code-review]\n",
+        )
+        .unwrap();
+        std::fs::write(presets_dir.join("broken.yaml"), "skills: [go]\n").unwrap();
+        std::fs::write(presets_dir.join("notes.txt"), "persona: x\n").unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let preset = registry.preset("go-review").expect("should load preset");
+        assert_eq!(preset.persona, "backend-engineer");
+        assert_eq!(preset.source, Source::Project);
+        assert!(registry.preset("broken").is_none());
+        assert!(registry.preset("notes").is_none());
+    }
+
     #[test]
     fn subdirectory_file_still_works() {
         let tmp = tempfile::tempdir().unwrap();
@@ -522,7 +884,7 @@ mod tests {
         .unwrap();
 
         let mut fragments: HashMap<Category, HashMap<String, Fragment>> = HashMap::new();
-        load_from_fs(&mut fragments, tmp.path(), Source::Project);
+        load_from_fs(&mut fragments, tmp.path(), Source::Project, Category::all());
 
         let frag = fragments
             .get(&Category::Tone)
@@ -530,4 +892,62 @@ mod tests {
             .expect("Subdirectory file should still load");
         assert_eq!(frag.description, "Friendly tone");
     }
+
+    #[test]
+    fn loads_custom_category_fragments() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.yaml"),
+            "categories:\n  - name: output-format\n    dir: output-formats\n",
+        )
+        .unwrap();
+        let dir = tmp.path().join("output-formats");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("json.md"), "Respond with JSON only.").unwrap();
+        std::fs::write(
+            tmp.path().join("yaml.md"),
+            "---\ncategory: output-format\n---\nRespond with YAML only.",
+        )
+        .unwrap();
+
+        let registry = Registry::new(Some(tmp.path().to_path_buf()));
+        let category = registry.category("output-format").unwrap();
+        assert!(registry.categories().contains(&category));
+        assert_eq!(
+            registry.names_for_category(category.clone()),
+            &["json".to_string(), "yaml".to_string()]
+        );
+        assert_eq!(registry.list(Some(category), None, None).len(), 2);
+    }
+
+    #[test]
+    fn custom_categories_are_per_registry() {
+        let first = tempfile::tempdir().unwrap();
+        std::fs::write(
+            first.path().join("config.yaml"),
+            "categories:\n  - name: output-format\n    dir: output-formats\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(first.path().join("output-formats")).unwrap();
+        std::fs::write(first.path().join("output-formats/json.md"), "JSON.").unwrap();
+        let second = tempfile::tempdir().unwrap();
+        std::fs::write(
+            second.path().join("config.yaml"),
+            "categories:\n  - name: output-format\n    dir: formats\n    heading: Formats\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(second.path().join("formats")).unwrap();
+        std::fs::write(second.path().join("formats/yaml.md"), "YAML.").unwrap();
+
+        let first = Registry::new(Some(first.path().to_path_buf()));
+        let second = Registry::new(Some(second.path().to_path_buf()));
+        let a = first.category("output-format").unwrap();
+        let b = second.category("output-format").unwrap();
+        assert_eq!(a.dir_name(), "output-formats");
+        assert_eq!(b.dir_name(), "formats");
+        assert_ne!(a, b);
+        assert_eq!(first.names_for_category(a), &["json".to_string()]);
+        assert_eq!(second.names_for_category(b), &["yaml".to_string()]);
+        assert!(first.category("formats").is_none());
+    }
 }
@@ -25,14 +25,14 @@ fn default_true() -> Option<bool> {
     Some(true)
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ResolveResult {
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
     pub meta: ResolveMeta,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct Node {
     pub id: String,
     pub category: String,
@@ -48,7 +48,7 @@ pub struct Node {
     pub source: String,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
 pub struct Edge {
     pub from: String,
     pub to: String,
@@ -56,7 +56,7 @@ pub struct Edge {
     pub label: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ResolveMeta {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<String>,
@@ -231,9 +231,14 @@ pub fn resolve(registry: &Registry, params: &ResolveParams) -> Result<ResolveRes
         }
     }
 
-    // Add contexts, tones, constraints
-    for category in &[Category::Context, Category::Tone, Category::Constraint] {
-        let fragments = registry.list(Some(*category), None, None);
+    // Add contexts, tones, constraints, examples
+    for category in &[
+        Category::Context,
+        Category::Tone,
+        Category::Constraint,
+        Category::Example,
+    ] {
+        let fragments = registry.list(Some(category.clone()), None, None);
         for f in fragments {
             // Tones always included; others filtered by tags if user provided tags
             if has_user_tags
@@ -543,6 +548,15 @@ mod tests {
             skill_groups: vec!["backend".to_string(), "general".to_string()],
             body: "Test body.".to_string(),
             source: Source::Project,
+            path: None,
+            shadows: vec![],
+            variants: Default::default(),
+            locale: None,
+            translations: Default::default(),
+            version: None,
+            since: None,
+            deprecated: false,
+            replaced_by: None,
         };
 
         let node = fragment_to_node(&frag);
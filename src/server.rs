@@ -1,29 +1,46 @@
-use crate::compose::{self, ComposeRequest};
-use crate::fragment::Category;
+use crate::authoring::{self, FragmentDraft, FragmentPatch};
+use crate::compose::{self, ComposeRequest, Composition, Conversation};
+use crate::config::Layout;
+use crate::diff::{self, ComposeDiff};
+use crate::example::Role;
+use crate::fragment::{Category, Fragment, Frontmatter, Source};
+use crate::lock;
 use crate::registry::Registry;
 use crate::resolve;
-use crate::version::VersionChecker;
+use crate::version::{VersionChecker, VersionInfo};
 use rmcp::handler::server::router::tool::ToolRouter;
-use rmcp::handler::server::wrapper::Parameters;
+use rmcp::handler::server::wrapper::{Json, Parameters};
 use rmcp::model::{
-    AnnotateAble, CompleteRequestParams, CompleteResult, CompletionInfo, GetPromptRequestParams,
-    GetPromptResult, Implementation, ListPromptsResult, ListResourcesResult,
-    PaginatedRequestParams, Prompt, PromptMessage, PromptMessageRole, RawResource,
-    ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities,
-    ServerInfo,
+    AnnotateAble, CallToolResult, CompleteRequestParams, CompleteResult, CompletionInfo, Content,
+    GetPromptRequestParams, GetPromptResult, Implementation, ListPromptsResult,
+    ListResourceTemplatesResult, ListResourcesResult, LoggingLevel,
+    LoggingMessageNotificationParam, Meta, PaginatedRequestParams, Prompt, PromptArgument,
+    PromptMessage, PromptMessageRole, RawResource, RawResourceTemplate, ReadResourceRequestParams,
+    ReadResourceResult, Resource, ResourceContents, ServerCapabilities, ServerInfo,
+    SetLevelRequestParams,
 };
 use rmcp::service::RequestContext;
-use rmcp::{tool, tool_handler, tool_router, RoleServer};
+use rmcp::{tool, tool_handler, tool_router, Peer, RoleServer};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+const RESOURCE_PAGE_SIZE: usize = 100;
 
 pub struct MimicServer {
     registry: Arc<RwLock<Registry>>,
+    // Additional per-project registries, keyed by project name
+    projects: BTreeMap<String, Arc<RwLock<Registry>>>,
     tool_router: ToolRouter<Self>,
     version_checker: Arc<VersionChecker>,
-    // Keep the watcher alive for the server's lifetime
+    // Refuse compositions whose fragments differ from the project's mimic.lock
+    locked: bool,
+    // Minimum level of log notifications, set by the client through logging/setLevel
+    log_level: Mutex<LoggingLevel>,
+    // Keep the watchers alive for the server's lifetime
     _watcher: Option<notify::RecommendedWatcher>,
+    _project_watchers: Vec<notify::RecommendedWatcher>,
 }
 
 impl MimicServer {
@@ -33,10 +50,240 @@ impl MimicServer {
     ) -> Self {
         Self {
             registry,
+            projects: BTreeMap::new(),
             tool_router: Self::tool_router(),
+            locked: false,
             version_checker: Arc::new(VersionChecker::new()),
+            log_level: Mutex::new(LoggingLevel::Info),
             _watcher: watcher,
+            _project_watchers: Vec::new(),
+        }
+    }
+
+    /// Registers an additional project registry, addressable by `name` through
+    /// the `project` parameter of the tools and `mimic://projects/{name}/...` URIs.
+    pub fn with_project(
+        mut self,
+        name: impl Into<String>,
+        registry: Arc<RwLock<Registry>>,
+        watcher: Option<notify::RecommendedWatcher>,
+    ) -> Self {
+        self.projects.insert(name.into(), registry);
+        self._project_watchers.extend(watcher);
+        self
+    }
+
+    /// Makes every composition check its fragments against the project's
+    /// `mimic.lock` and fail when one is missing or has changed.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    fn check_locked(
+        &self,
+        registry: &Registry,
+        req: &ComposeRequest,
+        layout: &Layout,
+    ) -> Result<(), String> {
+        if !self.locked {
+            return Ok(());
+        }
+        lock::verify_locked(registry, req, layout)
+    }
+
+    fn read_composition(
+        &self,
+        uri: &str,
+        req: &ComposeRequest,
+        project: Option<&str>,
+    ) -> Result<ReadResourceResult, rmcp::ErrorData> {
+        let invalid = |e: String| rmcp::ErrorData::invalid_params(e, None);
+        let registry = self.registry_for(project).map_err(invalid)?.read().unwrap();
+        self.check_locked(&registry, req, &registry.config().layout)
+            .map_err(invalid)?;
+        let text = compose::compose(&registry, req).map_err(invalid)?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("text/markdown".into()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    fn compose_explained(&self, params: ComposeParams) -> Result<Composition, String> {
+        self.with_compose_inputs(params, |registry, req, layout| {
+            compose::compose_with_layout(registry, req, layout)
+        })
+    }
+
+    fn compose_conversation(&self, params: ComposeParams) -> Result<Conversation, String> {
+        self.with_compose_inputs(params, |registry, req, layout| {
+            compose::compose_conversation(registry, req, layout)
+        })
+    }
+
+    /// Runs `f` with the request, registry and effective layout described by `params`.
+    fn with_compose_inputs<T>(
+        &self,
+        params: ComposeParams,
+        f: impl FnOnce(&Registry, &ComposeRequest, &Layout) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let req = ComposeRequest {
+            persona: params.persona,
+            skills: params.skills,
+            contexts: params.contexts,
+            tones: params.tones,
+            constraints: params.constraints,
+            examples: params.examples,
+            target: params.target,
+            locale: params.locale,
+            replace_deprecated: params.replace_deprecated,
+            custom: params.custom,
+        };
+        let registry = self
+            .registry_for(params.project.as_deref())?
+            .read()
+            .unwrap();
+        let mut layout = registry.config().layout.clone();
+        if let Some(overrides) = params.layout {
+            layout.merge(overrides, registry.categories());
         }
+        self.check_locked(&registry, &req, &layout)?;
+        f(&registry, &req, &layout)
+    }
+
+    /// Sends each warning to the client as a log notification, unless the
+    /// client asked for a stricter level.
+    async fn log_warnings(&self, peer: &Peer<RoleServer>, warnings: &[String]) {
+        if (*self.log_level.lock().unwrap() as u8) > (LoggingLevel::Warning as u8) {
+            return;
+        }
+        for warning in warnings {
+            // Logging is best-effort; a client that went away can't act on it anyway
+            let _ = peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Warning,
+                    logger: Some("mimic".into()),
+                    data: serde_json::Value::String(warning.clone()),
+                })
+                .await;
+        }
+    }
+
+    /// Returns the registry for `project`, or the default registry when `None`.
+    fn registry_for(&self, project: Option<&str>) -> Result<&RwLock<Registry>, String> {
+        match project {
+            None => Ok(&self.registry),
+            Some(name) => self
+                .projects
+                .get(name)
+                .map(|r| r.as_ref())
+                .ok_or_else(|| format!("Project '{}' not found", name)),
+        }
+    }
+
+    /// Composes the prompt `request` names, against the registry of the
+    /// `project` argument when there is one. Returns the warnings to log
+    /// alongside the result.
+    fn prompt(
+        &self,
+        request: GetPromptRequestParams,
+    ) -> (Result<GetPromptResult, rmcp::ErrorData>, Vec<String>) {
+        let args = request.arguments.unwrap_or_default();
+        let project = list_argument(&args, "project").into_iter().next();
+        let registry = match self.registry_for(project.as_deref()) {
+            Ok(registry) => registry.read().unwrap(),
+            Err(e) => {
+                let error = rmcp::ErrorData::new(rmcp::model::ErrorCode::INVALID_PARAMS, e, None);
+                return (Err(error), vec![]);
+            }
+        };
+
+        let preset = request
+            .name
+            .strip_prefix("mimic-preset-")
+            .and_then(|name| registry.preset(name));
+        let (mut req, description) = match preset {
+            Some(preset) => (
+                preset.to_request(),
+                preset
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("{} preset prompt", preset.name)),
+            ),
+            None => {
+                let persona_name = request.name.strip_prefix("mimic-").unwrap_or(&request.name);
+                let req = ComposeRequest {
+                    persona: persona_name.to_string(),
+                    skills: vec![],
+                    contexts: vec![],
+                    tones: vec![],
+                    constraints: vec![],
+                    examples: vec![],
+                    target: None,
+                    locale: None,
+                    replace_deprecated: false,
+                    custom: Default::default(),
+                };
+                (req, format!("{} persona prompt", persona_name))
+            }
+        };
+
+        extend_unique(&mut req.skills, list_argument(&args, "skills"));
+        extend_unique(&mut req.contexts, list_argument(&args, "contexts"));
+        // `tone` is the original single-tone argument, still accepted
+        let mut tones = list_argument(&args, "tones");
+        tones.extend(list_argument(&args, "tone"));
+        if tones.is_empty() && preset.is_none() {
+            tones.push("concise".to_string());
+        }
+        extend_unique(&mut req.tones, tones);
+        extend_unique(&mut req.constraints, list_argument(&args, "constraints"));
+        extend_unique(&mut req.examples, list_argument(&args, "examples"));
+        if let Some(target) = list_argument(&args, "target").into_iter().next() {
+            req.target = Some(target);
+        }
+        if let Some(locale) = list_argument(&args, "locale").into_iter().next() {
+            req.locale = Some(locale);
+        }
+
+        let layout = &registry.config().layout;
+        let mut warnings = Vec::new();
+        let result = match self
+            .check_locked(&registry, &req, layout)
+            .and_then(|()| compose::compose_conversation(&registry, &req, layout))
+        {
+            // The composed prompt is instructions for the model, which MCP
+            // prompts can only carry as a user message. Examples follow it
+            // as the exchanges they describe.
+            Ok(conversation) => {
+                warnings = conversation.warnings;
+                Ok(GetPromptResult {
+                    description: Some(description),
+                    messages: std::iter::once(PromptMessage::new_text(
+                        PromptMessageRole::User,
+                        conversation.system,
+                    ))
+                    .chain(conversation.messages.into_iter().map(|turn| {
+                        let role = match turn.role {
+                            Role::User => PromptMessageRole::User,
+                            Role::Assistant => PromptMessageRole::Assistant,
+                        };
+                        PromptMessage::new_text(role, turn.content)
+                    }))
+                    .collect(),
+                })
+            }
+            Err(e) => Err(rmcp::ErrorData::new(
+                rmcp::model::ErrorCode::INVALID_PARAMS,
+                e,
+                None,
+            )),
+        };
+        (result, warnings)
     }
 }
 
@@ -56,11 +303,52 @@ struct ComposeParams {
     /// Constraints to apply (e.g. ["no-frameworks"])
     #[serde(default)]
     constraints: Vec<String>,
+    /// Worked examples to include (e.g. ["code-review"])
+    #[serde(default)]
+    examples: Vec<String>,
+    /// Model the prompt is for (e.g. "small", "claude-sonnet-4"). Fragments with a
+    /// matching variant use it; the rest fall back to their default body.
+    #[serde(default)]
+    target: Option<String>,
+    /// Language to compose in (e.g. "ja", "pt-BR"). Fragments with a translation use
+    /// it; the rest keep their default language.
+    #[serde(default)]
+    locale: Option<String>,
+    /// Use the `replaced_by` fragment in place of each deprecated fragment requested.
+    /// Deprecated fragments are reported as warnings either way.
+    #[serde(default)]
+    replace_deprecated: bool,
+    /// Fragments from project-defined categories, keyed by category name (e.g. {"output-format": ["json"]})
+    #[serde(default)]
+    custom: BTreeMap<String, Vec<String>>,
+    /// Project to compose against (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+    /// Also return which fragment (category, name, source layer, file path, byte range)
+    /// produced each span of the prompt and why it was included.
+    #[serde(default)]
+    explain: bool,
+    /// Layout overrides for this request (section order, headings, preamble, epilogue),
+    /// applied on top of the project's config.yaml
+    #[serde(default)]
+    layout: Option<Layout>,
+    /// "text" (default) renders examples inline in the prompt; "messages" returns
+    /// {system, messages} with the examples as prior user/assistant turns.
+    #[serde(default)]
+    format: ComposeFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum ComposeFormat {
+    #[default]
+    Text,
+    Messages,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 struct ListParams {
-    /// Filter by category: persona, skill, context, tone, constraint
+    /// Filter by category: persona, skill, context, tone, constraint, example
     #[serde(default)]
     category: Option<String>,
     /// Filter by tag
@@ -69,6 +357,15 @@ struct ListParams {
     /// Filter by skill group: backend, frontend, mobile, infrastructure, data, security, general
     #[serde(default)]
     group: Option<String>,
+    /// Show descriptions in this language where a translation exists (e.g. "ja")
+    #[serde(default)]
+    locale: Option<String>,
+    /// Also list deprecated fragments
+    #[serde(default)]
+    include_deprecated: bool,
+    /// Project to list fragments from (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -81,18 +378,127 @@ struct RecommendParams {
     /// Filter recommendations by tags (e.g. ["security"]). Empty = no tag filter.
     #[serde(default)]
     tags: Vec<String>,
+    /// Also recommend deprecated fragments
+    #[serde(default)]
+    include_deprecated: bool,
+    /// Project to recommend from (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ResolveToolParams {
+    #[serde(flatten)]
+    params: resolve::ResolveParams,
+    /// Project to resolve against (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CreateFragmentParams {
+    /// Category of the new fragment: persona, skill, context, tone, constraint, example
+    category: String,
+    /// Fragment name, used as the file name (e.g. "payments-domain")
+    name: String,
+    /// Markdown body of the fragment
+    body: String,
+    /// One-line description shown by 'list' and 'recommend'
+    #[serde(default)]
+    description: Option<String>,
+    /// Tags for discovery (e.g. ["payments", "backend"])
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Skill group (skills only, e.g. "backend")
+    #[serde(default)]
+    group: Option<String>,
+    /// Seniority level (personas only, e.g. "senior")
+    #[serde(default)]
+    level: Option<String>,
+    /// Skill groups the persona draws from (personas only, e.g. ["backend", "data"])
+    #[serde(default)]
+    skill_groups: Vec<String>,
+    /// Layer to write to: "project" or "global". Defaults to project when a .mimic/ directory exists.
+    #[serde(default)]
+    layer: Option<String>,
+    /// Project to write into (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct UpdateFragmentParams {
+    /// Category of the fragment: persona, skill, context, tone, constraint, example
+    category: String,
+    /// Name of the fragment to update
+    name: String,
+    /// New markdown body. Omit to keep the current body.
+    #[serde(default)]
+    body: Option<String>,
+    /// New description. Omit to keep the current one.
+    #[serde(default)]
+    description: Option<String>,
+    /// Replacement tags. Omit to keep the current ones.
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+    /// New skill group. Omit to keep the current one.
+    #[serde(default)]
+    group: Option<String>,
+    /// New level. Omit to keep the current one.
+    #[serde(default)]
+    level: Option<String>,
+    /// Replacement skill groups. Omit to keep the current ones.
+    #[serde(default)]
+    skill_groups: Option<Vec<String>>,
+    /// Project containing the fragment (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DeleteFragmentParams {
+    /// Category of the fragment: persona, skill, context, tone, constraint, example
+    category: String,
+    /// Name of the fragment to delete
+    name: String,
+    /// Project containing the fragment (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetFragmentParams {
+    /// Category of the fragment: persona, skill, context, tone, constraint, example
+    category: String,
+    /// Fragment name (e.g. "go")
+    name: String,
+    /// Project to read from (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DiffParams {
+    /// Left-hand side: a preset name, persona name, or compose spec (e.g. "backend-engineer?skills=go,postgresql")
+    left: String,
+    /// Right-hand side, in the same forms as `left`
+    right: String,
+    /// Project to compose against (e.g. "services/backend"). Omit for the default project.
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 struct RecommendResult {
     persona: RecommendPersona,
     skills: Vec<RecommendFragment>,
     contexts: Vec<RecommendFragment>,
     tones: Vec<RecommendFragment>,
     constraints: Vec<RecommendFragment>,
+    examples: Vec<RecommendFragment>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct RecommendPersona {
     name: String,
     description: String,
@@ -101,7 +507,7 @@ struct RecommendPersona {
     skill_groups: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct RecommendFragment {
     name: String,
     description: String,
@@ -109,7 +515,7 @@ struct RecommendFragment {
     group: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct FragmentInfo {
     name: String,
     category: String,
@@ -121,27 +527,106 @@ struct FragmentInfo {
     level: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     skill_groups: Vec<String>,
+    /// Targets with their own variant body
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    variants: Vec<String>,
+    /// Locales with a translated body
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locales: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replaced_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ListResult {
+    fragments: Vec<FragmentInfo>,
+}
+
+impl From<&Fragment> for FragmentInfo {
+    fn from(f: &Fragment) -> Self {
+        FragmentInfo {
+            name: f.name.clone(),
+            category: f.category.to_string(),
+            description: f.description.clone(),
+            tags: f.tags.clone(),
+            group: f.group.clone(),
+            level: f.level.clone(),
+            skill_groups: f.skill_groups.clone(),
+            variants: f.variants.keys().cloned().collect(),
+            locales: f.translations.keys().cloned().collect(),
+            version: f.version.clone(),
+            deprecated: f.deprecated,
+            replaced_by: f.replaced_by.clone(),
+            since: f.since.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FragmentDetail {
+    #[serde(flatten)]
+    info: FragmentInfo,
+    source: Source,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    shadows: Vec<Source>,
+    body: String,
+}
+
+impl From<&Fragment> for FragmentDetail {
+    fn from(f: &Fragment) -> Self {
+        FragmentDetail {
+            info: FragmentInfo::from(f),
+            source: f.source,
+            path: f.path.as_ref().map(|p| p.display().to_string()),
+            shadows: f.shadows.clone(),
+            body: f.body.clone(),
+        }
+    }
 }
 
 #[tool_router]
 impl MimicServer {
     #[tool(
-        description = "Compose a system prompt from fragments. Combines a persona with optional skills, contexts, tones, and constraints into a structured system prompt. Use 'recommend' first to see available fragments for a persona."
+        description = "Compose a system prompt from fragments. Combines a persona with optional skills, contexts, tones, constraints, and examples into a structured system prompt. Set format to 'messages' to get the examples back as prior user/assistant turns instead of inline. Use 'recommend' first to see available fragments for a persona. Deprecated fragments are reported as warning log messages; set replace_deprecated to use their replacements instead."
     )]
-    fn compose(&self, Parameters(params): Parameters<ComposeParams>) -> Result<String, String> {
-        let req = ComposeRequest {
-            persona: params.persona,
-            skills: params.skills,
-            contexts: params.contexts,
-            tones: params.tones,
-            constraints: params.constraints,
+    async fn compose(
+        &self,
+        peer: Peer<RoleServer>,
+        Parameters(params): Parameters<ComposeParams>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        if params.format == ComposeFormat::Messages {
+            if params.explain {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "explain is only supported with format 'text'",
+                )]));
+            }
+            let conversation = match self.compose_conversation(params) {
+                Ok(c) => c,
+                Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+            };
+            self.log_warnings(&peer, &conversation.warnings).await;
+            let structured = serde_json::to_value(&conversation)
+                .map_err(|e| rmcp::ErrorData::internal_error(e.to_string(), None))?;
+            return Ok(CallToolResult::structured(structured));
+        }
+
+        let explain = params.explain;
+        let mut composition = match self.compose_explained(params) {
+            Ok(c) => c,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
         };
-        let registry = self.registry.read().unwrap();
-        let mut result = compose::compose(&registry, &req)?;
+        self.log_warnings(&peer, &composition.warnings).await;
 
         if let Some(info) = self.version_checker.cached() {
             if info.update_available {
-                result.push_str(&format!(
+                composition.prompt.push_str(&format!(
                     "\n\n---\n> Update available: mimic v{} (current: v{})",
                     info.latest.as_deref().unwrap_or("unknown"),
                     info.current,
@@ -149,13 +634,39 @@ impl MimicServer {
             }
         }
 
-        Ok(result)
+        if !explain {
+            return Ok(CallToolResult::success(vec![Content::text(
+                composition.prompt,
+            )]));
+        }
+
+        // The prompt stays the first text block so clients that only read
+        // text keep working; the provenance map and any warnings follow it.
+        let spans = serde_json::to_string_pretty(&composition.spans)
+            .map_err(|e| rmcp::ErrorData::internal_error(e.to_string(), None))?;
+        let structured = serde_json::to_value(&composition)
+            .map_err(|e| rmcp::ErrorData::internal_error(e.to_string(), None))?;
+        let mut content = vec![Content::text(composition.prompt), Content::text(spans)];
+        if !composition.warnings.is_empty() {
+            let warnings = composition
+                .warnings
+                .iter()
+                .map(|w| format!("warning: {}", w))
+                .collect::<Vec<_>>()
+                .join("\n");
+            content.push(Content::text(warnings));
+        }
+        Ok(CallToolResult {
+            content,
+            structured_content: Some(structured),
+            is_error: Some(false),
+            meta: None,
+        })
     }
 
     #[tool(description = "Check if a newer version of mimic is available")]
-    async fn check_update(&self) -> String {
-        let info = self.version_checker.check().await;
-        serde_json::to_string_pretty(&info).unwrap_or_else(|e| format!("error: {}", e))
+    async fn check_update(&self) -> Json<VersionInfo> {
+        Json(self.version_checker.check().await)
     }
 
     #[tool(
@@ -166,19 +677,23 @@ impl MimicServer {
     )]
     fn resolve(
         &self,
-        Parameters(params): Parameters<resolve::ResolveParams>,
-    ) -> Result<String, String> {
-        let registry = self.registry.read().unwrap();
-        let result = resolve::resolve(&registry, &params)?;
-        serde_json::to_string_pretty(&result)
-            .map_err(|e| format!("Failed to serialize resolve result: {}", e))
+        Parameters(ResolveToolParams { params, project }): Parameters<ResolveToolParams>,
+    ) -> Result<Json<resolve::ResolveResult>, String> {
+        let registry = self.registry_for(project.as_deref())?.read().unwrap();
+        resolve::resolve(&registry, &params).map(Json)
     }
 
     #[tool(
-        description = "Get recommended fragments for a persona. Returns a flat, categorized list of skills, contexts, tones, and constraints that match the persona's skill_groups. Use this before 'compose' to see what's available."
+        description = "Get recommended fragments for a persona. Returns a flat, categorized list of skills, contexts, tones, constraints, and examples that match the persona's skill_groups. Use this before 'compose' to see what's available."
     )]
-    fn recommend(&self, Parameters(params): Parameters<RecommendParams>) -> Result<String, String> {
-        let registry = self.registry.read().unwrap();
+    fn recommend(
+        &self,
+        Parameters(params): Parameters<RecommendParams>,
+    ) -> Result<Json<RecommendResult>, String> {
+        let registry = self
+            .registry_for(params.project.as_deref())?
+            .read()
+            .unwrap();
         let persona = registry
             .get(Category::Persona, &params.persona)
             .ok_or_else(|| format!("Persona '{}' not found", params.persona))?;
@@ -189,14 +704,16 @@ impl MimicServer {
             &params.groups
         };
 
-        let has_tag = |f: &crate::fragment::Fragment| -> bool {
+        let has_tag = |f: &Fragment| -> bool {
             params.tags.is_empty() || params.tags.iter().any(|t| f.tags.contains(t))
         };
+        let visible = |f: &Fragment| params.include_deprecated || !f.deprecated;
 
         // Skills: filter by effective groups (empty = all) and tags
         let skills: Vec<RecommendFragment> = registry
             .list(Some(Category::Skill), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| {
                 if effective_groups.is_empty() {
                     true
@@ -217,6 +734,7 @@ impl MimicServer {
         let contexts: Vec<RecommendFragment> = registry
             .list(Some(Category::Context), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| has_tag(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
@@ -229,6 +747,7 @@ impl MimicServer {
         let tones: Vec<RecommendFragment> = registry
             .list(Some(Category::Tone), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
                 description: f.description.clone(),
@@ -239,6 +758,19 @@ impl MimicServer {
         let constraints: Vec<RecommendFragment> = registry
             .list(Some(Category::Constraint), None, None)
             .into_iter()
+            .filter(|f| visible(f))
+            .filter(|f| has_tag(f))
+            .map(|f| RecommendFragment {
+                name: f.name.clone(),
+                description: f.description.clone(),
+                group: None,
+            })
+            .collect();
+
+        let examples: Vec<RecommendFragment> = registry
+            .list(Some(Category::Example), None, None)
+            .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| has_tag(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
@@ -258,32 +790,146 @@ impl MimicServer {
             contexts,
             tones,
             constraints,
+            examples,
         };
 
-        serde_json::to_string_pretty(&result)
-            .map_err(|e| format!("Failed to serialize recommend result: {}", e))
+        Ok(Json(result))
     }
 
     #[tool(
-        description = "List available fragments. Returns a JSON array of fragments with name, category, description, and tags. Use to browse all fragments or discover personas."
+        description = "List available fragments. Returns an object whose 'fragments' array holds each fragment's name, category, description, and tags. Use to browse all fragments or discover personas."
     )]
-    fn list(&self, Parameters(params): Parameters<ListParams>) -> String {
-        let category = params.category.as_deref().and_then(parse_category);
-        let registry = self.registry.read().unwrap();
+    fn list(&self, Parameters(params): Parameters<ListParams>) -> Result<Json<ListResult>, String> {
+        let registry = self
+            .registry_for(params.project.as_deref())?
+            .read()
+            .unwrap();
+        let category = params
+            .category
+            .as_deref()
+            .and_then(|c| parse_category(c, &registry));
         let fragments = registry.list(category, params.tag.as_deref(), params.group.as_deref());
-        let infos: Vec<FragmentInfo> = fragments
-            .into_iter()
-            .map(|f| FragmentInfo {
-                name: f.name.clone(),
-                category: f.category.to_string(),
-                description: f.description.clone(),
-                tags: f.tags.clone(),
-                group: f.group.clone(),
-                level: f.level.clone(),
-                skill_groups: f.skill_groups.clone(),
-            })
-            .collect();
-        serde_json::to_string_pretty(&infos).unwrap_or_else(|_| "[]".to_string())
+        let locale = params.locale.as_deref();
+        Ok(Json(ListResult {
+            fragments: fragments
+                .into_iter()
+                .filter(|f| params.include_deprecated || !f.deprecated)
+                .map(|f| {
+                    let mut info = FragmentInfo::from(f);
+                    info.description = f.localized(locale).description.clone();
+                    info
+                })
+                .collect(),
+        }))
+    }
+
+    #[tool(
+        description = "Compose two presets or compose specs and compare them. Returns the fragments added and removed between left and right plus a unified diff of the rendered prompts. Specs use the compose resource syntax, e.g. \"backend-engineer?skills=go&tones=concise\"."
+    )]
+    fn diff(
+        &self,
+        Parameters(params): Parameters<DiffParams>,
+    ) -> Result<Json<ComposeDiff>, String> {
+        let registry = self
+            .registry_for(params.project.as_deref())?
+            .read()
+            .unwrap();
+        let left = diff::resolve_spec(&registry, &params.left)?;
+        let right = diff::resolve_spec(&registry, &params.right)?;
+        diff::diff(&registry, &left, &right, &params.left, &params.right).map(Json)
+    }
+
+    #[tool(
+        description = "Get a single fragment with its frontmatter, full markdown body, source layer (builtin, global, project), file path, and any lower layers it shadows. Use this to read a fragment's text before deciding to compose it."
+    )]
+    fn get_fragment(
+        &self,
+        Parameters(params): Parameters<GetFragmentParams>,
+    ) -> Result<String, String> {
+        let registry = self
+            .registry_for(params.project.as_deref())?
+            .read()
+            .unwrap();
+        let category = require_category(&params.category, &registry)?;
+        let frag = registry
+            .get(category.clone(), &params.name)
+            .ok_or_else(|| format!("{} '{}' not found", category, params.name))?;
+        serde_json::to_string_pretty(&FragmentDetail::from(frag))
+            .map_err(|e| format!("Failed to serialize fragment: {}", e))
+    }
+
+    #[tool(
+        description = "Create a new fragment file in the project or global layer. The content is linted before writing and rejected on errors. Use this to save a reusable skill, context, or persona distilled from the current session."
+    )]
+    fn create_fragment(
+        &self,
+        Parameters(params): Parameters<CreateFragmentParams>,
+    ) -> Result<String, String> {
+        let lock = self.registry_for(params.project.as_deref())?;
+        let mut registry = lock.write().unwrap();
+        let category = require_category(&params.category, &registry)?;
+        let layer = match params.layer.as_deref() {
+            Some(l) => authoring::parse_layer(l)?,
+            None if registry.layer_dir(Source::Project).is_some() => Source::Project,
+            None => Source::Global,
+        };
+        let draft = FragmentDraft {
+            category,
+            name: params.name,
+            frontmatter: Frontmatter {
+                description: params.description,
+                tags: params.tags,
+                group: params.group,
+                level: params.level,
+                skill_groups: params.skill_groups,
+                ..Frontmatter::default()
+            },
+            body: params.body,
+        };
+        let result = authoring::create_fragment(&registry, layer, &draft)?;
+        registry.reload();
+        serde_json::to_string_pretty(&result)
+            .map_err(|e| format!("Failed to serialize create result: {}", e))
+    }
+
+    #[tool(
+        description = "Update an existing project or global fragment. Only the provided fields change. Built-in fragments cannot be updated; create an override with 'create_fragment' instead."
+    )]
+    fn update_fragment(
+        &self,
+        Parameters(params): Parameters<UpdateFragmentParams>,
+    ) -> Result<String, String> {
+        let lock = self.registry_for(params.project.as_deref())?;
+        let mut registry = lock.write().unwrap();
+        let category = require_category(&params.category, &registry)?;
+        let patch = FragmentPatch {
+            description: params.description,
+            tags: params.tags,
+            group: params.group,
+            level: params.level,
+            skill_groups: params.skill_groups,
+            body: params.body,
+        };
+        let result = authoring::update_fragment(&registry, category, &params.name, &patch)?;
+        registry.reload();
+        serde_json::to_string_pretty(&result)
+            .map_err(|e| format!("Failed to serialize update result: {}", e))
+    }
+
+    #[tool(
+        description = "Delete a project or global fragment file. Built-in fragments cannot be deleted. Deleting an override makes the fragment it shadowed visible again."
+    )]
+    fn delete_fragment(
+        &self,
+        Parameters(params): Parameters<DeleteFragmentParams>,
+    ) -> Result<String, String> {
+        let lock = self.registry_for(params.project.as_deref())?;
+        let mut registry = lock.write().unwrap();
+        let category = require_category(&params.category, &registry)?;
+        let result = authoring::delete_fragment(&registry, category, &params.name)?;
+        registry.reload();
+        serde_json::to_string_pretty(&result)
+            .map_err(|e| format!("Failed to serialize delete result: {}", e))
     }
 }
 
@@ -304,7 +950,10 @@ impl rmcp::ServerHandler for MimicServer {
                  Workflow:\n\
                  1. Call 'recommend' with a persona name to see available skills, contexts, tones, and constraints.\n\
                  2. Call 'compose' with the persona and your chosen fragments to build a system prompt.\n\
-                 3. Use 'list' to browse all fragments or discover personas.\n\n\
+                 3. Use 'list' to browse all fragments or discover personas.\n\
+                 4. Use 'create_fragment' to save a reusable fragment distilled from the session.\n\n\
+                 In a monorepo with several .mimic directories, pass 'project' (e.g. \"services/backend\") \
+                 to compose, list, recommend, or resolve to use that project's fragments.\n\n\
                  Example: recommend(persona: \"backend-engineer\") → pick skills → \
                  compose(persona: \"backend-engineer\", skills: [\"go\", \"postgresql\"], tones: [\"concise\"])"
                     .into(),
@@ -313,33 +962,116 @@ impl rmcp::ServerHandler for MimicServer {
                 .enable_tools()
                 .enable_resources()
                 .enable_prompts()
+                .enable_logging()
                 .build(),
             ..ServerInfo::default()
         }
     }
 
+    fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), rmcp::ErrorData>> + Send + '_ {
+        *self.log_level.lock().unwrap() = request.level;
+        std::future::ready(Ok(()))
+    }
+
     fn list_resources(
         &self,
-        _request: Option<PaginatedRequestParams>,
+        request: Option<PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ListResourcesResult, rmcp::ErrorData>> + Send + '_
     {
+        let offset = match request.and_then(|r| r.cursor) {
+            Some(cursor) => match cursor.parse::<usize>() {
+                Ok(offset) => offset,
+                Err(_) => {
+                    return std::future::ready(Err(rmcp::ErrorData::invalid_params(
+                        format!("Invalid cursor: {}", cursor),
+                        None,
+                    )))
+                }
+            },
+            None => 0,
+        };
         let registry = self.registry.read().unwrap();
-        let fragments = registry.list(None, None, None);
-        let resources = fragments
+        let mut resources: Vec<_> = registry
+            .list(None, None, None)
             .into_iter()
-            .map(|f| {
-                let mut raw = RawResource::new(
-                    format!("mimic://fragments/{}/{}", f.category.dir_name(), f.name),
-                    &f.name,
-                );
-                raw.description = Some(f.description.clone());
-                raw.mime_type = Some("text/markdown".into());
-                raw.no_annotation()
-            })
+            .map(|f| fragment_resource(f, None))
+            .collect();
+        // Project registries share built-in and global fragments with the
+        // default one, so only list what each project defines itself.
+        for (project, project_registry) in &self.projects {
+            let project_registry = project_registry.read().unwrap();
+            resources.extend(
+                project_registry
+                    .list(None, None, None)
+                    .into_iter()
+                    .filter(|f| f.source == Source::Project)
+                    .map(|f| fragment_resource(f, Some(project))),
+            );
+        }
+        let next_cursor = (offset + RESOURCE_PAGE_SIZE < resources.len())
+            .then(|| (offset + RESOURCE_PAGE_SIZE).to_string());
+        let resources = resources
+            .into_iter()
+            .skip(offset)
+            .take(RESOURCE_PAGE_SIZE)
             .collect();
         std::future::ready(Ok(ListResourcesResult {
             resources,
+            next_cursor,
+            meta: None,
+        }))
+    }
+
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListResourceTemplatesResult, rmcp::ErrorData>>
+           + Send
+           + '_ {
+        let template = |uri_template: &str, name: &str, description: &str| {
+            RawResourceTemplate {
+                uri_template: uri_template.into(),
+                name: name.into(),
+                title: None,
+                description: Some(description.into()),
+                mime_type: Some("text/markdown".into()),
+                icons: None,
+            }
+            .no_annotation()
+        };
+        let registry = self.registry.read().unwrap();
+        let dirs: Vec<&str> = registry.categories().iter().map(|c| c.dir_name()).collect();
+        let fragment = format!(
+            "A single fragment body. category is the plural directory name ({}).",
+            dirs.join(", ")
+        );
+        drop(registry);
+        let resource_templates = vec![
+            template(
+                "mimic://fragments/{category}/{name}",
+                "fragment",
+                &fragment,
+            ),
+            template(
+                "mimic://projects/{project}/fragments/{category}/{name}",
+                "project-fragment",
+                "A single fragment body resolved against a nested project.",
+            ),
+            template(
+                "mimic://compose/{persona}{?skills,contexts,tones,constraints,examples,target,locale,project}",
+                "composition",
+                "A composed system prompt. Query values are comma-separated fragment names, \
+                 e.g. mimic://compose/backend-engineer?skills=go,postgresql&tones=concise",
+            ),
+        ];
+        std::future::ready(Ok(ListResourceTemplatesResult {
+            resource_templates,
             next_cursor: None,
             meta: None,
         }))
@@ -351,11 +1083,23 @@ impl rmcp::ServerHandler for MimicServer {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ReadResourceResult, rmcp::ErrorData>> + Send + '_
     {
-        let registry = self.registry.read().unwrap();
-        let result = parse_resource_uri(&request.uri)
-            .and_then(|(cat, name)| registry.get(cat, name))
-            .map(|frag| ReadResourceResult {
-                contents: vec![ResourceContents::text(&frag.body, &request.uri)],
+        if let Some((req, project)) = parse_compose_uri(&request.uri) {
+            return std::future::ready(self.read_composition(
+                &request.uri,
+                &req,
+                project.as_deref(),
+            ));
+        }
+        let result = parse_scoped_resource_uri(&request.uri)
+            .and_then(|(project, dir, name)| {
+                let registry = self.registry_for(project).ok()?.read().unwrap();
+                let category = Category::lookup_dir(dir, registry.categories())?;
+                registry
+                    .get(category, name)
+                    .map(|frag| fragment_contents(frag, &request.uri))
+            })
+            .map(|contents| ReadResourceResult {
+                contents: vec![contents],
             })
             .ok_or_else(|| {
                 rmcp::ErrorData::resource_not_found(
@@ -372,48 +1116,68 @@ impl rmcp::ServerHandler for MimicServer {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<CompleteResult, rmcp::ErrorData>> + Send + '_
     {
-        let arg_name = &request.argument.name;
-        let prefix = &request.argument.value;
+        let arg_name = request.argument.name.as_str();
+        // Prompt arguments and compose template parameters take comma-separated
+        // lists: complete the last item and keep the ones before it.
+        let (head, prefix) = match request.argument.value.rsplit_once(',') {
+            Some((head, last)) => (format!("{head},"), last.trim_start()),
+            None => (String::new(), request.argument.value.as_str()),
+        };
+        let already: Vec<&str> = head.split(',').map(str::trim).collect();
 
-        let registry = self.registry.read().unwrap();
+        // Complete against the project the other arguments chose, if any
+        let project = request
+            .context
+            .as_ref()
+            .and_then(|c| c.get_argument("project"))
+            .filter(|p| !p.is_empty());
+        let Ok(registry) = self.registry_for(project.map(String::as_str)) else {
+            return std::future::ready(Ok(CompleteResult::default()));
+        };
+        let registry = registry.read().unwrap();
 
-        let category = match arg_name.as_str() {
+        let category = match arg_name {
             "persona" => Some(Category::Persona),
-            "skills" => Some(Category::Skill),
+            "skill" | "skills" => Some(Category::Skill),
             "context" | "contexts" => Some(Category::Context),
             "tone" | "tones" => Some(Category::Tone),
-            "constraints" => Some(Category::Constraint),
-            _ => None,
+            "constraint" | "constraints" => Some(Category::Constraint),
+            "example" | "examples" => Some(Category::Example),
+            // Fragment template: complete names within the chosen category
+            "name" => request
+                .context
+                .as_ref()
+                .and_then(|c| c.get_argument("category"))
+                .and_then(|c| registry.category(c)),
+            // Custom categories complete under their own name
+            other => registry.category(other).filter(|c| !c.is_builtin()),
         };
 
-        let values = if let Some(cat) = category {
+        let candidates: Vec<&str> = if let Some(cat) = category {
             registry
                 .names_for_category(cat)
                 .iter()
-                .filter(|n| n.starts_with(prefix))
-                .take(CompletionInfo::MAX_VALUES)
-                .cloned()
-                .collect()
-        } else if arg_name == "groups" {
-            registry
-                .all_groups()
-                .iter()
-                .filter(|g| g.starts_with(prefix))
-                .take(CompletionInfo::MAX_VALUES)
-                .cloned()
-                .collect()
-        } else if arg_name == "tags" {
-            registry
-                .all_tags()
-                .iter()
-                .filter(|t| t.starts_with(prefix))
-                .take(CompletionInfo::MAX_VALUES)
-                .cloned()
+                .map(String::as_str)
                 .collect()
         } else {
-            vec![]
+            match arg_name {
+                "groups" => registry.all_groups().iter().map(String::as_str).collect(),
+                "tags" => registry.all_tags().iter().map(String::as_str).collect(),
+                "target" => registry.all_targets(),
+                "locale" => registry.all_locales(),
+                "category" => registry.categories().iter().map(|c| c.dir_name()).collect(),
+                "project" => self.projects.keys().map(String::as_str).collect(),
+                _ => vec![],
+            }
         };
 
+        let values = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(prefix) && !already.contains(c))
+            .take(CompletionInfo::MAX_VALUES)
+            .map(|c| format!("{head}{c}"))
+            .collect();
+
         std::future::ready(Ok(CompleteResult {
             completion: CompletionInfo {
                 values,
@@ -423,6 +1187,9 @@ impl rmcp::ServerHandler for MimicServer {
         }))
     }
 
+    /// Lists a prompt per persona and preset of the default registry. The
+    /// `project` argument of a prompt composes it against a nested project
+    /// instead; presets only a nested project defines aren't listed.
     fn list_prompts(
         &self,
         _request: Option<PaginatedRequestParams>,
@@ -431,37 +1198,32 @@ impl rmcp::ServerHandler for MimicServer {
     {
         let registry = self.registry.read().unwrap();
         let personas = registry.names_for_category(Category::Persona);
-        let prompts = personas
+        let mut prompts: Vec<Prompt> = personas
             .iter()
-            .map(|name| {
-                use rmcp::model::PromptArgument;
-                Prompt {
-                    name: format!("mimic-{name}"),
-                    title: None,
-                    description: Some(format!("Compose the {name} persona prompt")),
-                    arguments: Some(vec![
-                        PromptArgument {
-                            name: "skills".into(),
-                            title: None,
-                            description: Some(
-                                "Comma-separated skill names (e.g. \"go,postgresql\")".into(),
-                            ),
-                            required: Some(false),
-                        },
-                        PromptArgument {
-                            name: "tone".into(),
-                            title: None,
-                            description: Some(
-                                "Tone to use (e.g. \"concise\"). Defaults to concise.".into(),
-                            ),
-                            required: Some(false),
-                        },
-                    ]),
-                    icons: None,
-                    meta: None,
-                }
+            .map(|name| Prompt {
+                name: format!("mimic-{name}"),
+                title: None,
+                description: Some(format!("Compose the {name} persona prompt")),
+                arguments: Some(compose_prompt_arguments(false)),
+                icons: None,
+                meta: None,
             })
             .collect();
+        prompts.extend(registry.presets().into_iter().map(|preset| {
+            Prompt {
+                name: format!("mimic-preset-{}", preset.name),
+                title: None,
+                description: Some(
+                    preset
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| format!("Compose the {} preset prompt", preset.name)),
+                ),
+                arguments: Some(compose_prompt_arguments(true)),
+                icons: None,
+                meta: None,
+            }
+        }));
         std::future::ready(Ok(ListPromptsResult {
             prompts,
             next_cursor: None,
@@ -472,62 +1234,173 @@ impl rmcp::ServerHandler for MimicServer {
     fn get_prompt(
         &self,
         request: GetPromptRequestParams,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<GetPromptResult, rmcp::ErrorData>> + Send + '_
     {
-        let persona_name = request.name.strip_prefix("mimic-").unwrap_or(&request.name);
-        let args = request.arguments.unwrap_or_default();
-        let skills: Vec<String> = args
-            .get("skills")
-            .and_then(|s| s.as_str())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
-            .unwrap_or_default();
-        let tones: Vec<String> = args
-            .get("tone")
-            .and_then(|s| s.as_str())
-            .filter(|s| !s.is_empty())
-            .map(|s| vec![s.to_string()])
-            .unwrap_or_else(|| vec!["concise".to_string()]);
-        let req = ComposeRequest {
-            persona: persona_name.to_string(),
-            skills,
-            contexts: vec![],
-            tones,
-            constraints: vec![],
-        };
-        let registry = self.registry.read().unwrap();
-        let result = match compose::compose(&registry, &req) {
-            Ok(text) => Ok(GetPromptResult {
-                description: Some(format!("{} persona prompt", persona_name)),
-                messages: vec![PromptMessage::new_text(PromptMessageRole::Assistant, text)],
-            }),
-            Err(e) => Err(rmcp::ErrorData::new(
-                rmcp::model::ErrorCode::INVALID_PARAMS,
-                e,
-                None,
-            )),
-        };
-        std::future::ready(result)
+        let (result, warnings) = self.prompt(request);
+        async move {
+            self.log_warnings(&context.peer, &warnings).await;
+            result
+        }
     }
 }
 
-fn parse_category(s: &str) -> Option<Category> {
+fn compose_prompt_arguments(additional: bool) -> Vec<PromptArgument> {
+    let lead = if additional {
+        "Additional comma-separated"
+    } else {
+        "Comma-separated"
+    };
+    let arg = |name: &str, what: &str, example: &str| PromptArgument {
+        name: name.into(),
+        title: None,
+        description: Some(format!("{lead} {what} names (e.g. \"{example}\")")),
+        required: Some(false),
+    };
+    let mut tones = arg("tones", "tone", "concise,pedagogical");
+    if !additional {
+        tones.description = tones
+            .description
+            .map(|d| format!("{d}. Defaults to concise."));
+    }
+    vec![
+        arg("skills", "skill", "go,postgresql"),
+        arg("contexts", "context", "code-review"),
+        tones,
+        arg("constraints", "constraint", "security-first"),
+        arg("examples", "example", "code-review"),
+        PromptArgument {
+            name: "target".into(),
+            title: None,
+            description: Some(
+                "Model to pick fragment variants for (e.g. \"small\" or \"claude\")".into(),
+            ),
+            required: Some(false),
+        },
+        PromptArgument {
+            name: "locale".into(),
+            title: None,
+            description: Some("Language to compose in (e.g. \"ja\" or \"pt-BR\")".into()),
+            required: Some(false),
+        },
+        PromptArgument {
+            name: "project".into(),
+            title: None,
+            description: Some(
+                "Nested project to compose against (e.g. \"services/backend\")".into(),
+            ),
+            required: Some(false),
+        },
+    ]
+}
+
+/// Reads a comma-separated prompt argument into trimmed, non-empty names.
+fn list_argument(args: &rmcp::model::JsonObject, key: &str) -> Vec<String> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| {
+            s.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn extend_unique(target: &mut Vec<String>, items: Vec<String>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
+        }
+    }
+}
+
+fn parse_category(s: &str, registry: &Registry) -> Option<Category> {
     match s {
         "persona" => Some(Category::Persona),
         "skill" => Some(Category::Skill),
         "context" => Some(Category::Context),
         "tone" => Some(Category::Tone),
         "constraint" => Some(Category::Constraint),
+        "example" => Some(Category::Example),
+        _ => registry.category(s).filter(|c| !c.is_builtin()),
+    }
+}
+
+fn fragment_resource(f: &Fragment, project: Option<&str>) -> Resource {
+    let uri = match project {
+        Some(project) => format!(
+            "mimic://projects/{}/fragments/{}/{}",
+            project,
+            f.category.dir_name(),
+            f.name
+        ),
+        None => format!("mimic://fragments/{}/{}", f.category.dir_name(), f.name),
+    };
+    let mut raw = RawResource::new(uri, &f.name);
+    raw.description = Some(f.description.clone());
+    raw.mime_type = Some("text/markdown".into());
+    raw.no_annotation()
+}
+
+fn require_category(s: &str, registry: &Registry) -> Result<Category, String> {
+    parse_category(s, registry).ok_or_else(|| {
+        format!(
+            "Unknown category '{}' (expected persona, skill, context, tone, constraint, example, or a custom category)",
+            s
+        )
+    })
+}
+
+/// Resource contents carry the markdown body as text and the remaining
+/// fragment metadata (source, path, shadows, frontmatter) in `_meta`.
+fn fragment_contents(frag: &Fragment, uri: &str) -> ResourceContents {
+    let meta = match serde_json::to_value(FragmentDetail::from(frag)) {
+        Ok(serde_json::Value::Object(mut obj)) => {
+            obj.remove("body");
+            Some(Meta(obj))
+        }
         _ => None,
+    };
+    ResourceContents::TextResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some("text/markdown".into()),
+        text: frag.body.clone(),
+        meta,
     }
 }
 
-fn parse_resource_uri(uri: &str) -> Option<(Category, &str)> {
+/// Parses `mimic://compose/{persona}?skills=a,b&tones=c` into a compose
+/// request and optional project name.
+fn parse_compose_uri(uri: &str) -> Option<(ComposeRequest, Option<String>)> {
+    compose::parse_spec(uri.strip_prefix("mimic://compose/")?)
+}
+
+fn parse_resource_uri(uri: &str) -> Option<(&str, &str)> {
     let path = uri.strip_prefix("mimic://fragments/")?;
-    let (cat_dir, name) = path.split_once('/')?;
-    let category = Category::from_dir_name(cat_dir)?;
-    Some((category, name))
+    parse_fragment_path(path)
+}
+
+/// Parses both `mimic://fragments/{category}/{name}` and the project-scoped
+/// `mimic://projects/{project}/fragments/{category}/{name}` form.
+fn parse_scoped_resource_uri(uri: &str) -> Option<(Option<&str>, &str, &str)> {
+    if let Some(rest) = uri.strip_prefix("mimic://projects/") {
+        // Project names may contain slashes, so split on the last `/fragments/`
+        let (project, path) = rest.rsplit_once("/fragments/")?;
+        if project.is_empty() {
+            return None;
+        }
+        let (category, name) = parse_fragment_path(path)?;
+        return Some((Some(project), category, name));
+    }
+    let (category, name) = parse_resource_uri(uri)?;
+    Some((None, category, name))
+}
+
+/// Splits `{category dir}/{name}`; the registry serving the resource
+/// resolves the directory, since custom categories differ per project.
+fn parse_fragment_path(path: &str) -> Option<(&str, &str)> {
+    path.split_once('/').filter(|(dir, _)| !dir.is_empty())
 }
 
 #[cfg(test)]
@@ -536,48 +1409,63 @@ mod tests {
 
     #[test]
     fn parse_category_persona() {
-        assert_eq!(parse_category("persona"), Some(Category::Persona));
+        assert_eq!(
+            parse_category("persona", &Registry::new(None)),
+            Some(Category::Persona)
+        );
     }
 
     #[test]
     fn parse_category_skill() {
-        assert_eq!(parse_category("skill"), Some(Category::Skill));
+        assert_eq!(
+            parse_category("skill", &Registry::new(None)),
+            Some(Category::Skill)
+        );
     }
 
     #[test]
     fn parse_category_context() {
-        assert_eq!(parse_category("context"), Some(Category::Context));
+        assert_eq!(
+            parse_category("context", &Registry::new(None)),
+            Some(Category::Context)
+        );
     }
 
     #[test]
     fn parse_category_tone() {
-        assert_eq!(parse_category("tone"), Some(Category::Tone));
+        assert_eq!(
+            parse_category("tone", &Registry::new(None)),
+            Some(Category::Tone)
+        );
     }
 
     #[test]
     fn parse_category_constraint() {
-        assert_eq!(parse_category("constraint"), Some(Category::Constraint));
+        assert_eq!(
+            parse_category("constraint", &Registry::new(None)),
+            Some(Category::Constraint)
+        );
     }
 
     #[test]
     fn parse_category_invalid() {
-        assert_eq!(parse_category("unknown"), None);
-        assert_eq!(parse_category(""), None);
-        assert_eq!(parse_category("Persona"), None);
+        assert_eq!(parse_category("unknown", &Registry::new(None)), None);
+        assert_eq!(parse_category("", &Registry::new(None)), None);
+        assert_eq!(parse_category("Persona", &Registry::new(None)), None);
     }
 
     #[test]
     fn parse_resource_uri_valid() {
         let (cat, name) =
             parse_resource_uri("mimic://fragments/personas/backend-engineer").unwrap();
-        assert_eq!(cat, Category::Persona);
+        assert_eq!(cat, "personas");
         assert_eq!(name, "backend-engineer");
     }
 
     #[test]
     fn parse_resource_uri_valid_skill() {
         let (cat, name) = parse_resource_uri("mimic://fragments/skills/go").unwrap();
-        assert_eq!(cat, Category::Skill);
+        assert_eq!(cat, "skills");
         assert_eq!(name, "go");
     }
 
@@ -592,9 +1480,81 @@ mod tests {
         assert!(parse_resource_uri("mimic://fragments/personas").is_none());
     }
 
+    #[test]
+    fn parse_scoped_resource_uri_unscoped() {
+        let (project, cat, name) =
+            parse_scoped_resource_uri("mimic://fragments/skills/go").unwrap();
+        assert_eq!(project, None);
+        assert_eq!(cat, "skills");
+        assert_eq!(name, "go");
+    }
+
+    #[test]
+    fn parse_scoped_resource_uri_nested_project() {
+        let (project, cat, name) = parse_scoped_resource_uri(
+            "mimic://projects/services/backend/fragments/personas/api-owner",
+        )
+        .unwrap();
+        assert_eq!(project, Some("services/backend"));
+        assert_eq!(cat, "personas");
+        assert_eq!(name, "api-owner");
+    }
+
+    #[test]
+    fn parse_scoped_resource_uri_missing_project() {
+        assert!(parse_scoped_resource_uri("mimic://projects//fragments/skills/go").is_none());
+        assert!(parse_scoped_resource_uri("mimic://projects/fragments/skills/go").is_none());
+    }
+
+    #[test]
+    fn parse_compose_uri_with_query() {
+        let (req, project) = parse_compose_uri(
+            "mimic://compose/backend-engineer?skills=go,postgresql&tones=concise&project=services%2Fbackend",
+        )
+        .unwrap();
+        assert_eq!(req.persona, "backend-engineer");
+        assert_eq!(req.skills, vec!["go", "postgresql"]);
+        assert_eq!(req.tones, vec!["concise"]);
+        assert!(req.contexts.is_empty());
+        assert!(req.constraints.is_empty());
+        assert_eq!(project.as_deref(), Some("services/backend"));
+    }
+
+    #[test]
+    fn parse_compose_uri_persona_only() {
+        let (req, project) = parse_compose_uri("mimic://compose/designer").unwrap();
+        assert_eq!(req.persona, "designer");
+        assert!(req.skills.is_empty());
+        assert!(project.is_none());
+    }
+
+    #[test]
+    fn parse_compose_uri_rejects_other_uris() {
+        assert!(parse_compose_uri("mimic://compose/").is_none());
+        assert!(parse_compose_uri("mimic://fragments/skills/go").is_none());
+    }
+
+    #[test]
+    fn list_argument_splits_and_trims() {
+        let args: rmcp::model::JsonObject =
+            serde_json::from_value(serde_json::json!({"skills": "go, postgresql,,", "tone": ""}))
+                .unwrap();
+        assert_eq!(list_argument(&args, "skills"), vec!["go", "postgresql"]);
+        assert!(list_argument(&args, "tone").is_empty());
+        assert!(list_argument(&args, "missing").is_empty());
+    }
+
+    #[test]
+    fn extend_unique_skips_duplicates() {
+        let mut target = vec!["go".to_string()];
+        extend_unique(&mut target, vec!["go".to_string(), "rust".to_string()]);
+        assert_eq!(target, vec!["go", "rust"]);
+    }
+
     #[test]
     fn parse_resource_uri_invalid_category() {
-        assert!(parse_resource_uri("mimic://fragments/unknown/test").is_none());
+        let (dir, _) = parse_resource_uri("mimic://fragments/unknown/test").unwrap();
+        assert!(Category::lookup_dir(dir, Category::all()).is_none());
     }
 
     fn recommend(
@@ -607,6 +1567,8 @@ mod tests {
             persona: persona.to_string(),
             groups,
             tags,
+            include_deprecated: false,
+            project: None,
         };
         let p = registry
             .get(Category::Persona, &params.persona)
@@ -618,13 +1580,15 @@ mod tests {
             &params.groups
         };
 
-        let has_tag = |f: &crate::fragment::Fragment| -> bool {
+        let has_tag = |f: &Fragment| -> bool {
             params.tags.is_empty() || params.tags.iter().any(|t| f.tags.contains(t))
         };
+        let visible = |f: &Fragment| params.include_deprecated || !f.deprecated;
 
         let skills: Vec<RecommendFragment> = registry
             .list(Some(Category::Skill), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| {
                 if effective_groups.is_empty() {
                     true
@@ -645,6 +1609,7 @@ mod tests {
         let contexts: Vec<RecommendFragment> = registry
             .list(Some(Category::Context), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| has_tag(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
@@ -656,6 +1621,7 @@ mod tests {
         let tones: Vec<RecommendFragment> = registry
             .list(Some(Category::Tone), None, None)
             .into_iter()
+            .filter(|f| visible(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
                 description: f.description.clone(),
@@ -666,6 +1632,19 @@ mod tests {
         let constraints: Vec<RecommendFragment> = registry
             .list(Some(Category::Constraint), None, None)
             .into_iter()
+            .filter(|f| visible(f))
+            .filter(|f| has_tag(f))
+            .map(|f| RecommendFragment {
+                name: f.name.clone(),
+                description: f.description.clone(),
+                group: None,
+            })
+            .collect();
+
+        let examples: Vec<RecommendFragment> = registry
+            .list(Some(Category::Example), None, None)
+            .into_iter()
+            .filter(|f| visible(f))
             .filter(|f| has_tag(f))
             .map(|f| RecommendFragment {
                 name: f.name.clone(),
@@ -685,6 +1664,7 @@ mod tests {
             contexts,
             tones,
             constraints,
+            examples,
         })
     }
 
@@ -776,7 +1756,10 @@ mod tests {
             let uri = format!("mimic://fragments/{}/test-name", cat.dir_name());
             let (parsed_cat, parsed_name) =
                 parse_resource_uri(&uri).unwrap_or_else(|| panic!("Failed to parse URI: {}", uri));
-            assert_eq!(parsed_cat, *cat);
+            assert_eq!(
+                Category::lookup_dir(parsed_cat, Category::all()),
+                Some(cat.clone())
+            );
             assert_eq!(parsed_name, "test-name");
         }
     }
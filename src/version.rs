@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::sync::Mutex;
 use std::time::Instant;
@@ -7,7 +8,7 @@ const CARGO_TOML_URL: &str = "https://raw.githubusercontent.com/ohmyjersh/mimic-
 const REQUEST_TIMEOUT_SECS: u64 = 5;
 const DEFAULT_TTL_SECS: u64 = 3600;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct VersionInfo {
     pub current: String,
     pub latest: Option<String>,
@@ -0,0 +1,32 @@
+//! File watching shared by the server's hot reload and `mimic lint --watch`.
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+
+/// Watches each of `dirs` recursively and calls `on_change` with the paths
+/// every filesystem event touches. Returns `None`, after printing a warning,
+/// when the platform watcher can't be set up. Dropping the watcher stops it.
+pub fn watch_dirs(
+    dirs: &[PathBuf],
+    mut on_change: impl FnMut(Vec<PathBuf>) + Send + 'static,
+) -> Option<RecommendedWatcher> {
+    let mut watcher = match notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+        if let Ok(event) = res {
+            on_change(event.paths);
+        }
+    }) {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("mimic: warning: failed to set up file watcher: {}", e);
+            return None;
+        }
+    };
+
+    for dir in dirs {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+            eprintln!("mimic: warning: failed to watch {}: {}", dir.display(), e);
+        }
+    }
+
+    Some(watcher)
+}
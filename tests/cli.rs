@@ -49,6 +49,207 @@ fn lint_bad_fragments_exits_nonzero() {
     );
 }
 
+#[test]
+fn lint_json_format_reports_diagnostics() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join(".mimic").join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    std::fs::write(
+        skills_dir.join("empty.md"),
+        "---\ndescription: Empty\n---\n",
+    )
+    .unwrap();
+
+    let output = mimic_bin()
+        .args(["lint", "--format", "json"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let diagnostic = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["file"] == ".mimic/skills/empty.md")
+        .unwrap()
+        .clone();
+    assert_eq!(diagnostic["rule"], "non-empty-body");
+    assert_eq!(diagnostic["severity"], "error");
+    assert!(report["errors"].as_u64().unwrap() >= 1);
+}
+
+#[test]
+fn lint_fix_rewrites_files_or_shows_a_diff() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join(".mimic").join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    let file = skills_dir.join("payments.md");
+    let original = "---\ntag: [Payments]\ngroup: backend\n---\nKnow the payments domain.\n";
+    std::fs::write(&file, original).unwrap();
+    // Keep the fixes away from any real global fragments
+    let lint = |args: &[&str]| {
+        mimic_bin()
+            .arg("lint")
+            .args(args)
+            .current_dir(tmp.path())
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = lint(&["--fix", "--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("+++ b/.mimic/skills/payments.md"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("+tags: [payments]"), "{stdout}");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), original);
+
+    // Machine-readable reports keep stdout to themselves and describe the
+    // file as it is, not as the fixes would leave it
+    let output = lint(&["--fix", "--dry-run", "--warnings", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rules: Vec<&str> = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|d| d["rule"].as_str())
+        .collect();
+    assert!(rules.contains(&"unknown-fields"), "{rules:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("+tags: [payments]"), "{stderr}");
+
+    let output = lint(&["--fix", "--warnings"]);
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "---\ndescription: Know the payments domain.\ntags: [payments]\ngroup: backend\n---\nKnow the payments domain.\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("payments.md:"), "{stderr}");
+
+    let output = lint(&["--dry-run"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn lint_rules_follow_config_and_rule_flag() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mimic_dir = tmp.path().join(".mimic");
+    std::fs::create_dir_all(mimic_dir.join("personas")).unwrap();
+    std::fs::write(
+        mimic_dir.join("personas/lead.md"),
+        "---\ndescription: Lead\ntags: [lead]\nskill_groups: [backend]\n---\nYou lead.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        mimic_dir.join("config.yaml"),
+        "lint:\n  rules:\n    persona-has-level: off\n    has-description: error\n",
+    )
+    .unwrap();
+    let lint = |args: &[&str]| {
+        let output = mimic_bin()
+            .args(["lint", "--warnings", "--format", "json"])
+            .args(args)
+            .current_dir(tmp.path())
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|d| d["file"] == ".mimic/personas/lead.md")
+            .map(|d| d["rule"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(lint(&[]).is_empty());
+    assert_eq!(
+        lint(&["--rule", "persona-has-level"]),
+        vec!["persona-has-level"]
+    );
+
+    let output = mimic_bin()
+        .args(["lint", "--rule", "no-such-rule"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn lint_checks_given_paths_and_the_global_layer() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join("home");
+    let project = tmp.path().join("project");
+    for dir in [
+        home.join(".mimic/skills"),
+        project.join(".mimic/skills"),
+        project.join("fragments/skills"),
+    ] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    let empty = "---\ndescription: Empty\n---\n";
+    std::fs::write(home.join(".mimic/skills/global-empty.md"), empty).unwrap();
+    std::fs::write(project.join(".mimic/skills/a.md"), empty).unwrap();
+    std::fs::write(project.join(".mimic/skills/b.md"), empty).unwrap();
+    std::fs::write(project.join("fragments/skills/c.md"), empty).unwrap();
+    let files = |args: &[&str]| {
+        let output = mimic_bin()
+            .args(["lint", "--format", "json"])
+            .args(args)
+            .current_dir(&project)
+            .env("HOME", &home)
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut files: Vec<String> = report["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["file"].as_str().unwrap().to_string())
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    };
+
+    // The global layer is ~/.mimic, the same directory the server loads.
+    // Files outside the current directory are named by their absolute path
+    // so SARIF and GitHub annotations point at them.
+    let global = home
+        .canonicalize()
+        .unwrap()
+        .join(".mimic/skills/global-empty.md");
+    assert_eq!(
+        files(&["--no-builtin"]),
+        vec![
+            ".mimic/skills/a.md".to_string(),
+            ".mimic/skills/b.md".to_string(),
+            global.display().to_string(),
+        ]
+    );
+    assert_eq!(files(&[".mimic/skills/a.md"]), vec![".mimic/skills/a.md"]);
+    assert_eq!(files(&["fragments"]), vec!["fragments/skills/c.md"]);
+    assert_eq!(
+        files(&["fragments/skills/c.md"]),
+        vec!["fragments/skills/c.md"]
+    );
+
+    let output = mimic_bin()
+        .args(["lint", "no-such-file.md"])
+        .current_dir(&project)
+        .env("HOME", &home)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
 #[test]
 fn help_flag() {
     let output = mimic_bin().args(["--help"]).output().unwrap();
@@ -70,3 +271,185 @@ fn version_flag() {
         "version should contain 'mimic': {stdout}"
     );
 }
+
+#[test]
+fn diff_identical_specs_exits_zero() {
+    let output = mimic_bin()
+        .args([
+            "diff",
+            "backend-engineer?skills=go",
+            "backend-engineer?skills=go",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No differences"));
+}
+
+#[test]
+fn diff_presets_reports_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let presets = tmp.path().join(".mimic").join("presets");
+    std::fs::create_dir_all(&presets).unwrap();
+    std::fs::write(
+        presets.join("old.yaml"),
+        "persona: backend-engineer\nskills: [go, postgresql]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        presets.join("new.yaml"),
+        "persona: backend-engineer\nskills: [go, rust]\n",
+    )
+    .unwrap();
+
+    let output = mimic_bin()
+        .args(["diff", "old", "new"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  - skill/postgresql"), "{stdout}");
+    assert!(stdout.contains("  + skill/rust"), "{stdout}");
+    assert!(stdout.contains("--- old\n+++ new\n@@ "), "{stdout}");
+}
+
+#[test]
+fn diff_unknown_operand_exits_two() {
+    let output = mimic_bin()
+        .args(["diff", "backend-engineer", "no-such-preset"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn lint_walks_custom_category_dirs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mimic_dir = tmp.path().join(".mimic");
+    let glossary = mimic_dir.join("glossary");
+    std::fs::create_dir_all(&glossary).unwrap();
+    std::fs::write(
+        mimic_dir.join("config.yaml"),
+        "categories:\n  - name: glossary\n",
+    )
+    .unwrap();
+    // Empty body is a lint error, so the file must be visited to fail
+    std::fs::write(glossary.join("empty.md"), "---\ndescription: Empty\n---\n").unwrap();
+
+    let output = mimic_bin()
+        .args(["lint"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(".mimic/glossary/empty.md"), "{stderr}");
+}
+
+#[test]
+fn lock_writes_and_checks_lockfile() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join(".mimic").join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    std::fs::write(skills_dir.join("payments.md"), "Know the payments domain.").unwrap();
+
+    let output = mimic_bin()
+        .args(["lock", "backend-engineer?skills=payments"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "lock should exit 0: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lock = std::fs::read_to_string(tmp.path().join(".mimic/mimic.lock")).unwrap();
+    assert!(lock.contains("skill/payments"), "{lock}");
+    assert!(lock.contains("backend-engineer?skills=payments"), "{lock}");
+
+    let check = || {
+        mimic_bin()
+            .args(["lock", "--check"])
+            .current_dir(tmp.path())
+            .output()
+            .unwrap()
+    };
+    assert!(check().status.success());
+
+    std::fs::write(skills_dir.join("payments.md"), "Know the billing domain.").unwrap();
+    let output = check();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("~ skill/payments"), "{stdout}");
+
+    std::fs::write(
+        tmp.path().join(".mimic/config.yaml"),
+        "layout:\n  headings: {skill: Skills}\n",
+    )
+    .unwrap();
+    let output = check();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("~ layout"), "{stdout}");
+
+    // --locked configures the server, so subcommands reject it
+    let output = mimic_bin()
+        .args(["--locked", "lock"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn lint_watch_relints_changed_files() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::Duration;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join("home");
+    let skills = tmp.path().join("project/.mimic/skills");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::create_dir_all(&skills).unwrap();
+
+    let mut child = mimic_bin()
+        .args(["lint", "--watch", "--no-builtin"])
+        .current_dir(tmp.path().join("project"))
+        .env("HOME", &home)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stderr = child.stderr.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let _ = tx.send(line);
+        }
+    });
+    let wait_for = |needle: &str| {
+        let mut seen = Vec::new();
+        while let Ok(line) = rx.recv_timeout(Duration::from_secs(10)) {
+            if line.contains(needle) {
+                return seen;
+            }
+            seen.push(line);
+        }
+        panic!("never saw {needle:?} in {seen:#?}");
+    };
+
+    wait_for("watching for changes");
+    std::fs::write(skills.join("empty.md"), "---\ndescription: Empty\n---\n").unwrap();
+    wait_for("re-linting .mimic/skills/empty.md");
+    let output = wait_for("error(s)");
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(
+        output
+            .iter()
+            .any(|l| l.contains(".mimic/skills/empty.md") && l.contains("error")),
+        "{output:#?}"
+    );
+}
@@ -1,13 +1,67 @@
 use mimic_ai::registry::Registry;
 use mimic_ai::server::MimicServer;
-use rmcp::{service::RunningService, RoleClient, ServiceExt};
+use rmcp::model::LoggingMessageNotificationParam;
+use rmcp::service::NotificationContext;
+use rmcp::{service::RunningService, ClientHandler, RoleClient, ServiceExt};
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 pub async fn spawn_server(project_dir: Option<PathBuf>) -> RunningService<RoleClient, ()> {
+    spawn_server_with_projects(project_dir, vec![]).await
+}
+
+#[allow(dead_code)]
+pub async fn spawn_server_with_projects(
+    project_dir: Option<PathBuf>,
+    projects: Vec<(&str, PathBuf)>,
+) -> RunningService<RoleClient, ()> {
+    let registry = Arc::new(RwLock::new(Registry::new(project_dir)));
+    let mut handler = MimicServer::new(registry, None);
+    for (name, dir) in projects {
+        let project_registry = Arc::new(RwLock::new(Registry::new(Some(dir))));
+        handler = handler.with_project(name, project_registry, None);
+    }
+    connect(handler, ()).await
+}
+
+/// Spawns a server that refuses compositions not matching `.mimic/mimic.lock`.
+#[allow(dead_code)]
+pub async fn spawn_locked_server(project_dir: PathBuf) -> RunningService<RoleClient, ()> {
+    let registry = Arc::new(RwLock::new(Registry::new(Some(project_dir))));
+    connect(MimicServer::new(registry, None).locked(true), ()).await
+}
+
+/// A client that records the log notifications the server sends it.
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub struct LogCollector {
+    pub messages: Arc<Mutex<Vec<LoggingMessageNotificationParam>>>,
+}
+
+impl ClientHandler for LogCollector {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.messages.lock().unwrap().push(params);
+    }
+}
+
+#[allow(dead_code)]
+pub async fn spawn_logging_server(
+    project_dir: Option<PathBuf>,
+) -> (RunningService<RoleClient, LogCollector>, LogCollector) {
     let registry = Arc::new(RwLock::new(Registry::new(project_dir)));
-    let handler = MimicServer::new(registry, None);
+    let collector = LogCollector::default();
+    let client = connect(MimicServer::new(registry, None), collector.clone()).await;
+    (client, collector)
+}
 
+async fn connect<C: ClientHandler>(
+    handler: MimicServer,
+    client: C,
+) -> RunningService<RoleClient, C> {
     let (client_stream, server_stream) = tokio::io::duplex(4096);
 
     // Spawn the server in the background
@@ -17,5 +71,5 @@ pub async fn spawn_server(project_dir: Option<PathBuf>) -> RunningService<RoleCl
     });
 
     // Connect a client
-    ().serve(client_stream).await.unwrap()
+    client.serve(client_stream).await.unwrap()
 }
@@ -0,0 +1,243 @@
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument, Exit, Initialized,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Initialize, Request as _, Shutdown};
+use lsp_types::{
+    CompletionResponse, Diagnostic, Hover, HoverContents, NumberOrString, PublishDiagnosticsParams,
+    Uri,
+};
+use serde_json::json;
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A client talking to an in-process `mimic lsp`.
+struct Client {
+    connection: Connection,
+    server: Option<JoinHandle<Result<(), String>>>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start(project_dir: &Path) -> Client {
+        let (server, connection) = Connection::memory();
+        let project_dir = project_dir.to_path_buf();
+        let server = std::thread::spawn(move || mimic_ai::lsp::serve(server, Some(project_dir)));
+        let mut client = Client {
+            connection,
+            server: Some(server),
+            next_id: 0,
+        };
+        client.request(Initialize::METHOD, json!({ "capabilities": {} }));
+        client.notify(Initialized::METHOD, json!({}));
+        client
+    }
+
+    fn request(&mut self, method: &str, params: serde_json::Value) -> serde_json::Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), method.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        loop {
+            match self.receive() {
+                Message::Response(r) if r.id == id => {
+                    return r
+                        .response_result
+                        .unwrap_or_else(|e| panic!("{method} failed: {e:?}"));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn notify(&self, method: &str, params: serde_json::Value) {
+        let notification = Notification::new(method.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    /// Waits for the diagnostics the server publishes next.
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        loop {
+            if let Message::Notification(n) = self.receive() {
+                if n.method == PublishDiagnostics::METHOD {
+                    let params: PublishDiagnosticsParams =
+                        serde_json::from_value(n.params).unwrap();
+                    return params.diagnostics;
+                }
+            }
+        }
+    }
+
+    fn receive(&self) -> Message {
+        self.connection
+            .receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("server did not answer")
+    }
+
+    fn shutdown(mut self) {
+        self.request(Shutdown::METHOD, serde_json::Value::Null);
+        self.notify(Exit::METHOD, serde_json::Value::Null);
+        let result = self.server.take().unwrap().join().unwrap();
+        assert_eq!(result, Ok(()));
+    }
+}
+
+fn uri(path: &Path) -> Uri {
+    format!("file://{}", path.display()).parse().unwrap()
+}
+
+fn rules(diagnostics: &[Diagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter_map(|d| match &d.code {
+            Some(NumberOrString::String(rule)) => Some(rule.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn project() -> tempfile::TempDir {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills = tmp.path().join(".mimic/skills");
+    std::fs::create_dir_all(&skills).unwrap();
+    std::fs::write(
+        skills.join("payments.md"),
+        "---\ndescription: Payment flows and reconciliation\ntags: [payments]\ngroup: backend\n---\nYou know card networks and ledgers.\n",
+    )
+    .unwrap();
+    tmp
+}
+
+#[test]
+fn publishes_lint_diagnostics_for_open_documents() {
+    let tmp = project();
+    let path = tmp.path().join(".mimic/skills/draft.md");
+    let client = Client::start(&tmp.path().join(".mimic"));
+
+    client.notify(
+        DidOpenTextDocument::METHOD,
+        json!({ "textDocument": {
+            "uri": uri(&path), "languageId": "markdown", "version": 1,
+            "text": "---\ndescription: Draft\ntags: [payments]\ngroup: backend\n---\n",
+        }}),
+    );
+    let diagnostics = client.diagnostics();
+    assert!(
+        rules(&diagnostics).contains(&"non-empty-body".to_string()),
+        "{diagnostics:#?}"
+    );
+
+    // Diagnostics follow the unsaved text
+    client.notify(
+        DidChangeTextDocument::METHOD,
+        json!({
+            "textDocument": { "uri": uri(&path), "version": 2 },
+            "contentChanges": [{ "text": "---\ndescription: Draft\ntags: [payments]\ngroup: backend\ntag: x\n---\nBody\n" }],
+        }),
+    );
+    let diagnostics = client.diagnostics();
+    assert_eq!(rules(&diagnostics), vec!["unknown-fields"]);
+    assert_eq!(diagnostics[0].range.start.line, 4);
+
+    client.shutdown();
+}
+
+#[test]
+fn reloads_config_when_a_file_is_saved() {
+    let tmp = project();
+    let path = tmp.path().join(".mimic/skills/draft.md");
+    let client = Client::start(&tmp.path().join(".mimic"));
+    let text = "---\ndescription: Draft\ntags: [payments]\ngroup: backend\ntag: x\n---\nBody\n";
+    client.notify(
+        DidOpenTextDocument::METHOD,
+        json!({ "textDocument": {
+            "uri": uri(&path), "languageId": "markdown", "version": 1, "text": text,
+        }}),
+    );
+    assert_eq!(rules(&client.diagnostics()), vec!["unknown-fields"]);
+
+    // Edits are linted against the config loaded when the file was opened
+    let config = tmp.path().join(".mimic/config.yaml");
+    std::fs::write(&config, "lint:\n  rules:\n    unknown-fields: off\n").unwrap();
+    let change = json!({
+        "textDocument": { "uri": uri(&path), "version": 2 },
+        "contentChanges": [{ "text": text }],
+    });
+    client.notify(DidChangeTextDocument::METHOD, change);
+    assert_eq!(rules(&client.diagnostics()), vec!["unknown-fields"]);
+
+    client.notify(
+        DidSaveTextDocument::METHOD,
+        json!({ "textDocument": { "uri": uri(&config) } }),
+    );
+    client.notify(
+        DidChangeTextDocument::METHOD,
+        json!({
+            "textDocument": { "uri": uri(&path), "version": 3 },
+            "contentChanges": [{ "text": text }],
+        }),
+    );
+    assert!(client.diagnostics().is_empty());
+
+    client.shutdown();
+}
+
+#[test]
+fn completes_frontmatter_and_hovers_references() {
+    let tmp = project();
+    let path = tmp.path().join(".mimic/skills/billing.md");
+    let text = "---\ndescription: Billing\ntags: [pay\ngroup: back\nreplaced_by: payments\nskill_\n---\nBody\n";
+    let mut client = Client::start(&tmp.path().join(".mimic"));
+    client.notify(
+        DidOpenTextDocument::METHOD,
+        json!({ "textDocument": {
+            "uri": uri(&path), "languageId": "markdown", "version": 1, "text": text,
+        }}),
+    );
+    client.diagnostics();
+
+    let mut complete = |line: u32, character: u32| {
+        let result = client.request(
+            Completion::METHOD,
+            json!({
+                "textDocument": { "uri": uri(&path) },
+                "position": { "line": line, "character": character },
+            }),
+        );
+        match serde_json::from_value::<Option<CompletionResponse>>(result).unwrap() {
+            Some(CompletionResponse::Array(items)) => {
+                items.into_iter().map(|i| i.label).collect::<Vec<_>>()
+            }
+            other => panic!("unexpected completions: {other:?}"),
+        }
+    };
+
+    assert!(complete(2, 10).contains(&"payments".to_string()));
+    assert!(complete(3, 11).contains(&"backend".to_string()));
+    assert_eq!(complete(5, 6), vec!["skill_groups"]);
+    let references = complete(4, 13);
+    assert!(references.contains(&"payments".to_string()));
+    assert!(!references.contains(&"billing".to_string()));
+
+    let result = client.request(
+        HoverRequest::METHOD,
+        json!({
+            "textDocument": { "uri": uri(&path) },
+            "position": { "line": 4, "character": 16 },
+        }),
+    );
+    let hover: Hover = serde_json::from_value(result).unwrap();
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("expected markdown hover");
+    };
+    assert!(
+        markup.value.contains("Payment flows and reconciliation"),
+        "{}",
+        markup.value
+    );
+
+    client.shutdown();
+}
@@ -1,6 +1,10 @@
 mod common;
 
-use rmcp::model::{CallToolRequestParams, GetPromptRequestParams, ReadResourceRequestParams};
+use rmcp::model::{
+    ArgumentInfo, CallToolRequestParams, CompleteRequestParams, CompletionContext,
+    GetPromptRequestParams, LoggingLevel, PaginatedRequestParams, PromptMessageRole,
+    ReadResourceRequestParams, Reference, ResourceContents, SetLevelRequestParams,
+};
 use std::borrow::Cow;
 
 #[tokio::test(flavor = "current_thread")]
@@ -25,7 +29,18 @@ async fn list_tools_returns_three_tools() {
         names.contains(&"check_update"),
         "missing check_update tool: {names:?}"
     );
-    assert_eq!(tools.len(), 5);
+    for authoring in ["create_fragment", "update_fragment", "delete_fragment"] {
+        assert!(
+            names.contains(&authoring),
+            "missing {authoring} tool: {names:?}"
+        );
+    }
+    assert!(
+        names.contains(&"get_fragment"),
+        "missing get_fragment tool: {names:?}"
+    );
+    assert!(names.contains(&"diff"), "missing diff tool: {names:?}");
+    assert_eq!(tools.len(), 10);
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -91,6 +106,87 @@ async fn compose_full_prompt() {
     );
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn compose_explain_returns_provenance() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "persona": "backend-engineer",
+                    "skills": ["go"],
+                    "explain": true
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let structured = result
+        .structured_content
+        .expect("explain structured content");
+    let prompt = structured["prompt"].as_str().unwrap();
+    let spans = structured["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 2);
+    let go = &spans[1];
+    assert_eq!(go["category"], "skill");
+    assert_eq!(go["name"], "go");
+    assert_eq!(go["source"], "builtin");
+    assert_eq!(go["reason"], "requested");
+    let (start, end) = (
+        go["start"].as_u64().unwrap() as usize,
+        go["end"].as_u64().unwrap() as usize,
+    );
+    assert!(prompt[..start].ends_with("## Expertise\n\n"));
+    assert_eq!(end, prompt.len());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn compose_examples_inline_and_as_messages() {
+    let client = common::spawn_server(None).await;
+    let call = |format: &str| CallToolRequestParams {
+        meta: None,
+        name: Cow::Borrowed("compose"),
+        arguments: Some(
+            serde_json::from_value(serde_json::json!({
+                "persona": "backend-engineer",
+                "examples": ["code-review"],
+                "format": format
+            }))
+            .unwrap(),
+        ),
+        task: None,
+    };
+
+    let inline = client.call_tool(call("text")).await.unwrap();
+    let text = inline.content[0].as_text().unwrap().text.clone();
+    assert!(
+        text.contains("## Examples\n\n**User:** Can you review this?"),
+        "{text}"
+    );
+    assert!(
+        text.contains("**Assistant:** One issue worth fixing"),
+        "{text}"
+    );
+
+    let messages = client.call_tool(call("messages")).await.unwrap();
+    assert!(!messages.is_error.unwrap_or(false));
+    let structured = messages.structured_content.expect("conversation");
+    assert!(!structured["system"]
+        .as_str()
+        .unwrap()
+        .contains("## Examples"));
+    let turns = structured["messages"].as_array().unwrap();
+    assert_eq!(turns.len(), 2);
+    assert_eq!(turns[0]["role"], "user");
+    assert!(turns[0]["content"].as_str().unwrap().contains("```python"));
+    assert_eq!(turns[1]["role"], "assistant");
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn compose_missing_persona_returns_error() {
     let client = common::spawn_server(None).await;
@@ -160,6 +256,61 @@ async fn list_filtered_by_category() {
     assert!(!text.contains("\"persona\""), "should not contain personas");
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn data_tools_declare_output_schemas() {
+    let client = common::spawn_server(None).await;
+    let tools = client.list_all_tools().await.unwrap();
+    for name in ["list", "recommend", "resolve", "check_update", "diff"] {
+        let tool = tools.iter().find(|t| t.name == name).unwrap();
+        let schema = tool
+            .output_schema
+            .as_ref()
+            .unwrap_or_else(|| panic!("{name} should declare an output schema"));
+        assert_eq!(schema.get("type"), Some(&serde_json::json!("object")));
+    }
+    let compose = tools.iter().find(|t| t.name == "compose").unwrap();
+    assert!(compose.output_schema.is_none(), "compose returns markdown");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn list_and_recommend_return_structured_content() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("list"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({ "category": "persona" })).unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    let structured = result.structured_content.expect("list structured content");
+    let fragments = structured["fragments"].as_array().unwrap();
+    assert!(fragments
+        .iter()
+        .any(|f| f["name"] == "backend-engineer" && f["category"] == "persona"));
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("recommend"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({ "persona": "backend-engineer" }))
+                    .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    let structured = result
+        .structured_content
+        .expect("recommend structured content");
+    assert_eq!(structured["persona"]["name"], "backend-engineer");
+    assert!(!structured["tones"].as_array().unwrap().is_empty());
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn resolve_with_persona() {
     let client = common::spawn_server(None).await;
@@ -185,6 +336,10 @@ async fn resolve_with_persona() {
     );
     assert!(text.contains("nodes"), "should have nodes field");
     assert!(text.contains("edges"), "should have edges field");
+    let structured = result
+        .structured_content
+        .expect("resolve structured content");
+    assert!(structured["meta"]["node_count"].as_u64().unwrap() > 0);
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -289,3 +444,843 @@ async fn compose_with_project_local_fragment() {
         "should use project-local fragment"
     );
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn compose_target_uses_fragment_variants() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    std::fs::write(skills_dir.join("house.md"), "Long house rules.").unwrap();
+    std::fs::write(skills_dir.join("house.small.md"), "Short house rules.").unwrap();
+
+    let client = common::spawn_server(Some(tmp.path().to_path_buf())).await;
+    let compose = |target: Option<&str>| CallToolRequestParams {
+        meta: None,
+        name: Cow::Borrowed("compose"),
+        arguments: Some(
+            serde_json::from_value(serde_json::json!({
+                "persona": "backend-engineer",
+                "skills": ["house"],
+                "target": target
+            }))
+            .unwrap(),
+        ),
+        task: None,
+    };
+    let text =
+        |result: rmcp::model::CallToolResult| result.content[0].as_text().unwrap().text.clone();
+
+    let default = text(client.call_tool(compose(None)).await.unwrap());
+    assert!(default.ends_with("Long house rules."), "{default}");
+    let small = text(client.call_tool(compose(Some("small"))).await.unwrap());
+    assert!(small.ends_with("Short house rules."), "{small}");
+
+    let list = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("list"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({ "category": "skill" })).unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    let fragments = list.structured_content.unwrap()["fragments"].clone();
+    let house = fragments
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == "house")
+        .unwrap();
+    assert_eq!(house["variants"], serde_json::json!(["small"]));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn locale_selects_translations_in_prompts_and_list() {
+    let tmp = tempfile::tempdir().unwrap();
+    let personas_dir = tmp.path().join("personas");
+    std::fs::create_dir_all(&personas_dir).unwrap();
+    std::fs::write(
+        personas_dir.join("backend-engineer.pt-BR.md"),
+        "---\ndescription: Engenheira de backend sênior\n---\nVocê é uma engenheira de backend sênior. Responda em português.",
+    )
+    .unwrap();
+
+    let client = common::spawn_server(Some(tmp.path().to_path_buf())).await;
+    let args = serde_json::json!({ "locale": "pt" });
+    let prompt = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-backend-engineer".to_string(),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    let text = format!("{:?}", prompt.messages[0].content);
+    assert!(text.contains("Responda em português"), "{text}");
+
+    let list = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("list"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "category": "persona",
+                    "locale": "pt-BR"
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    let fragments = list.structured_content.unwrap()["fragments"].clone();
+    let persona = fragments
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == "backend-engineer")
+        .unwrap()
+        .clone();
+    assert_eq!(persona["description"], "Engenheira de backend sênior");
+    assert_eq!(persona["locales"], serde_json::json!(["pt-BR"]));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn deprecated_fragments_are_hidden_and_warned_about() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    std::fs::write(
+        skills_dir.join("legacy-go.md"),
+        "---\ndescription: Old Go advice\ngroup: backend\ndeprecated: true\nreplaced_by: go\n---\nOld Go advice.",
+    )
+    .unwrap();
+
+    let (client, logs) = common::spawn_logging_server(Some(tmp.path().to_path_buf())).await;
+    let call = |name: &'static str, args: serde_json::Value| {
+        client.call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed(name),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            task: None,
+        })
+    };
+    let names = |result: &rmcp::model::CallToolResult, key: &str| -> Vec<String> {
+        result.structured_content.as_ref().unwrap()[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let list = call("list", serde_json::json!({ "category": "skill" }))
+        .await
+        .unwrap();
+    assert!(!names(&list, "fragments").contains(&"legacy-go".to_string()));
+    let list = call(
+        "list",
+        serde_json::json!({ "category": "skill", "include_deprecated": true }),
+    )
+    .await
+    .unwrap();
+    assert!(names(&list, "fragments").contains(&"legacy-go".to_string()));
+    let recommend = call(
+        "recommend",
+        serde_json::json!({ "persona": "backend-engineer" }),
+    )
+    .await
+    .unwrap();
+    assert!(!names(&recommend, "skills").contains(&"legacy-go".to_string()));
+
+    let result = call(
+        "compose",
+        serde_json::json!({ "persona": "backend-engineer", "skills": ["legacy-go"], "explain": true }),
+    )
+    .await
+    .unwrap();
+    let structured = result.structured_content.unwrap();
+    assert_eq!(
+        structured["warnings"],
+        serde_json::json!(["Skill 'legacy-go' is deprecated; use 'go' instead"])
+    );
+    let text = format!("{:?}", result.content[2]);
+    assert!(
+        text.contains("warning: Skill 'legacy-go' is deprecated"),
+        "{text}"
+    );
+    let logged = logs.messages.lock().unwrap().clone();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0].level, LoggingLevel::Warning);
+
+    // Clients that only want errors don't get the warning
+    client
+        .set_level(SetLevelRequestParams {
+            meta: None,
+            level: LoggingLevel::Error,
+        })
+        .await
+        .unwrap();
+    let result = call(
+        "compose",
+        serde_json::json!({ "persona": "backend-engineer", "skills": ["legacy-go"], "replace_deprecated": true }),
+    )
+    .await
+    .unwrap();
+    let text = format!("{:?}", result.content[0]);
+    assert!(!text.contains("Old Go advice"), "{text}");
+    assert_eq!(logs.messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn locked_server_rejects_changed_fragments() {
+    let tmp = tempfile::tempdir().unwrap();
+    let skills_dir = tmp.path().join("skills");
+    std::fs::create_dir_all(&skills_dir).unwrap();
+    std::fs::write(skills_dir.join("payments.md"), "Know the payments domain.").unwrap();
+    let registry = mimic_ai::registry::Registry::new(Some(tmp.path().to_path_buf()));
+    mimic_ai::lock::Lockfile::generate(&registry, &["backend-engineer?skills=payments".into()])
+        .unwrap()
+        .save(tmp.path())
+        .unwrap();
+
+    let client = common::spawn_locked_server(tmp.path().to_path_buf()).await;
+    let compose = |skills: serde_json::Value| {
+        client.call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(
+                    serde_json::json!({ "persona": "backend-engineer", "skills": skills }),
+                )
+                .unwrap(),
+            ),
+            task: None,
+        })
+    };
+
+    let result = compose(serde_json::json!(["payments"])).await.unwrap();
+    assert_ne!(result.is_error, Some(true));
+
+    let result = compose(serde_json::json!(["go"])).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    let text = format!("{:?}", result.content[0]);
+    assert!(text.contains("skill/go is not locked"), "{text}");
+}
+
+fn write_project_persona(dir: &std::path::Path, name: &str, body: &str) {
+    let personas_dir = dir.join("personas");
+    std::fs::create_dir_all(&personas_dir).unwrap();
+    std::fs::write(
+        personas_dir.join(format!("{name}.md")),
+        format!("---\ndescription: {name}\ntags: [test]\nlevel: senior\nskill_groups: [general]\n---\n{body}"),
+    )
+    .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn compose_with_project_parameter() {
+    let backend = tempfile::tempdir().unwrap();
+    let mobile = tempfile::tempdir().unwrap();
+    write_project_persona(
+        backend.path(),
+        "service-owner",
+        "You own the backend service.",
+    );
+    write_project_persona(mobile.path(), "service-owner", "You own the mobile app.");
+
+    let client = common::spawn_server_with_projects(
+        None,
+        vec![
+            ("services/backend", backend.path().to_path_buf()),
+            ("mobile", mobile.path().to_path_buf()),
+        ],
+    )
+    .await;
+
+    for (project, expected) in [
+        ("services/backend", "backend service"),
+        ("mobile", "mobile app"),
+    ] {
+        let result = client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: Cow::Borrowed("compose"),
+                arguments: Some(
+                    serde_json::from_value(serde_json::json!({
+                        "persona": "service-owner",
+                        "project": project
+                    }))
+                    .unwrap(),
+                ),
+                task: None,
+            })
+            .await
+            .unwrap();
+        assert!(!result.is_error.unwrap_or(false));
+        let text = format!("{:?}", result.content);
+        assert!(
+            text.contains(expected),
+            "{project} should use its own fragment"
+        );
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn prompts_and_completion_use_the_project_argument() {
+    let backend = tempfile::tempdir().unwrap();
+    write_project_persona(
+        backend.path(),
+        "service-owner",
+        "You own the backend service.",
+    );
+    let client = common::spawn_server_with_projects(
+        None,
+        vec![("services/backend", backend.path().to_path_buf())],
+    )
+    .await;
+
+    let args = serde_json::json!({ "project": "services/backend" });
+    let prompt = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-service-owner".to_string(),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    let text = format!("{:?}", prompt.messages[0].content);
+    assert!(text.contains("backend service"), "{text}");
+
+    let args = serde_json::json!({ "project": "nonexistent" });
+    let err = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-backend-engineer".to_string(),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            meta: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Project 'nonexistent' not found"));
+
+    let complete = |project: Option<&str>| {
+        let context = project.map(|p| {
+            CompletionContext::with_arguments(
+                [("project".to_string(), p.to_string())]
+                    .into_iter()
+                    .collect(),
+            )
+        });
+        client.complete(CompleteRequestParams {
+            meta: None,
+            r#ref: Reference::for_prompt("mimic-backend-engineer"),
+            argument: ArgumentInfo {
+                name: "persona".to_string(),
+                value: "service".to_string(),
+            },
+            context,
+        })
+    };
+    let result = complete(Some("services/backend")).await.unwrap();
+    assert_eq!(result.completion.values, vec!["service-owner"]);
+    let result = complete(None).await.unwrap();
+    assert!(result.completion.values.is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn compose_unknown_project_returns_error() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "persona": "backend-engineer",
+                    "project": "nonexistent"
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(result.is_error.unwrap_or(false), "should be an error");
+    let text = format!("{:?}", result.content);
+    assert!(text.contains("Project 'nonexistent' not found"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn project_scoped_resources() {
+    let backend = tempfile::tempdir().unwrap();
+    write_project_persona(
+        backend.path(),
+        "service-owner",
+        "You own the backend service.",
+    );
+
+    let client = common::spawn_server_with_projects(
+        None,
+        vec![("services/backend", backend.path().to_path_buf())],
+    )
+    .await;
+
+    let uri = "mimic://projects/services/backend/fragments/personas/service-owner";
+    let resources = client.list_all_resources().await.unwrap();
+    assert!(
+        resources.iter().any(|r| r.uri == uri),
+        "should list project-scoped resource"
+    );
+
+    let result = client
+        .read_resource(ReadResourceRequestParams {
+            uri: uri.to_string(),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    let text = format!("{:?}", result.contents);
+    assert!(text.contains("backend service"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn create_fragment_is_immediately_composable() {
+    let tmp = tempfile::tempdir().unwrap();
+    let client = common::spawn_server(Some(tmp.path().to_path_buf())).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("create_fragment"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "category": "skill",
+                    "name": "session-skill",
+                    "description": "Distilled from a session",
+                    "tags": ["test"],
+                    "group": "backend",
+                    "body": "You know the payments domain."
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false), "{:?}", result.content);
+    assert!(tmp.path().join("skills/session-skill.md").is_file());
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "persona": "backend-engineer",
+                    "skills": ["session-skill"]
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let text = format!("{:?}", result.content);
+    assert!(text.contains("payments domain"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn delete_fragment_refuses_builtin() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("delete_fragment"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "category": "skill",
+                    "name": "go"
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(result.is_error.unwrap_or(false), "should be an error");
+    let text = format!("{:?}", result.content);
+    assert!(text.contains("built-in"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn get_fragment_returns_body_and_layers() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_project_persona(
+        tmp.path(),
+        "backend-engineer",
+        "You are our backend engineer.",
+    );
+
+    let client = common::spawn_server(Some(tmp.path().to_path_buf())).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("get_fragment"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "category": "persona",
+                    "name": "backend-engineer"
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let text = result.content[0].as_text().unwrap().text.clone();
+    let detail: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(detail["source"], "project");
+    assert_eq!(detail["body"], "You are our backend engineer.");
+    assert_eq!(detail["level"], "senior");
+    assert!(detail["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("backend-engineer.md"));
+    assert_eq!(detail["shadows"], serde_json::json!(["builtin"]));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn read_resource_includes_metadata() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .read_resource(ReadResourceRequestParams {
+            uri: "mimic://fragments/skills/go".to_string(),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    match &result.contents[0] {
+        ResourceContents::TextResourceContents {
+            mime_type, meta, ..
+        } => {
+            assert_eq!(mime_type.as_deref(), Some("text/markdown"));
+            let meta = meta.as_ref().expect("should carry metadata");
+            assert_eq!(meta.0["source"], "builtin");
+            assert_eq!(meta.0["group"], "backend");
+        }
+        other => panic!("unexpected contents: {other:?}"),
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn list_resources_paginates() {
+    let client = common::spawn_server(None).await;
+    let first = client.list_resources(None).await.unwrap();
+    let cursor = first
+        .next_cursor
+        .clone()
+        .expect("built-ins should span more than one page");
+    let second = client
+        .list_resources(Some(PaginatedRequestParams {
+            meta: None,
+            cursor: Some(cursor),
+        }))
+        .await
+        .unwrap();
+    assert_ne!(first.resources[0].uri, second.resources[0].uri);
+
+    let all = client.list_all_resources().await.unwrap();
+    assert!(all.len() > first.resources.len());
+    let unique: std::collections::HashSet<_> = all.iter().map(|r| r.uri.clone()).collect();
+    assert_eq!(unique.len(), all.len(), "pages should not overlap");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn list_resource_templates_advertises_uris() {
+    let client = common::spawn_server(None).await;
+    let templates = client.list_all_resource_templates().await.unwrap();
+    let uris: Vec<&str> = templates.iter().map(|t| t.uri_template.as_str()).collect();
+    assert!(uris.contains(&"mimic://fragments/{category}/{name}"));
+    assert!(uris
+        .iter()
+        .any(|u| u.starts_with("mimic://compose/{persona}")));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn read_compose_resource_returns_prompt() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .read_resource(ReadResourceRequestParams {
+            uri: "mimic://compose/backend-engineer?skills=go,postgresql&tones=concise".to_string(),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    let text = format!("{:?}", result.contents);
+    assert!(text.contains("## Expertise"));
+    assert!(text.contains("## Communication Style"));
+
+    let err = client
+        .read_resource(ReadResourceRequestParams {
+            uri: "mimic://compose/backend-engineer?skills=nonexistent-xyz".to_string(),
+            meta: None,
+        })
+        .await;
+    assert!(err.is_err(), "unknown skill should fail");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn get_prompt_accepts_contexts_constraints_and_tones() {
+    let client = common::spawn_server(None).await;
+    let args = serde_json::json!({
+        "skills": "go",
+        "contexts": "code-review",
+        "tones": "concise,pedagogical",
+        "constraints": "security-first"
+    });
+    let result = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-backend-engineer".to_string(),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.messages[0].role, PromptMessageRole::User);
+    let text = format!("{:?}", result.messages[0].content);
+    for section in ["Expertise", "Context", "Communication Style", "Constraints"] {
+        assert!(text.contains(section), "missing {section} section");
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn get_prompt_appends_example_turns() {
+    let client = common::spawn_server(None).await;
+    let args = serde_json::json!({ "examples": "clarifying-question" });
+    let result = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-backend-engineer".to_string(),
+            arguments: Some(serde_json::from_value(args).unwrap()),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    let roles: Vec<_> = result.messages.iter().map(|m| m.role.clone()).collect();
+    assert_eq!(
+        roles,
+        vec![
+            PromptMessageRole::User,
+            PromptMessageRole::User,
+            PromptMessageRole::Assistant
+        ]
+    );
+    let system = format!("{:?}", result.messages[0].content);
+    assert!(!system.contains("## Examples"), "{system}");
+    let question = format!("{:?}", result.messages[1].content);
+    assert!(question.contains("Make the import faster."), "{question}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn preset_prompts_are_listed_and_composed() {
+    let tmp = tempfile::tempdir().unwrap();
+    let presets_dir = tmp.path().join("presets");
+    std::fs::create_dir_all(&presets_dir).unwrap();
+    std::fs::write(
+        presets_dir.join("go-review.yaml"),
+        "description: Go code review\npersona: backend-engineer\nskills: [go]\ncontexts: [code-review]\n",
+    )
+    .unwrap();
+
+    let client = common::spawn_server(Some(tmp.path().to_path_buf())).await;
+    let prompts = client.list_all_prompts().await.unwrap();
+    assert!(prompts.iter().any(|p| p.name == "mimic-preset-go-review"));
+
+    let result = client
+        .get_prompt(GetPromptRequestParams {
+            name: "mimic-preset-go-review".to_string(),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({"constraints": "security-first"}))
+                    .unwrap(),
+            ),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.description.as_deref(), Some("Go code review"));
+    let text = format!("{:?}", result.messages[0].content);
+    assert!(text.contains("## Expertise"));
+    assert!(text.contains("## Context"));
+    assert!(text.contains("## Constraints"));
+    assert!(
+        !text.contains("## Communication Style"),
+        "presets should not get the default tone"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn complete_prompt_list_argument() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .complete(CompleteRequestParams {
+            meta: None,
+            r#ref: Reference::for_prompt("mimic-backend-engineer"),
+            argument: ArgumentInfo {
+                name: "skills".to_string(),
+                value: "go,postg".to_string(),
+            },
+            context: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.completion.values, vec!["go,postgresql"]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn diff_tool_compares_compositions() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("diff"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "left": "backend-engineer?skills=go",
+                    "right": "backend-engineer?skills=go,rust&tones=concise"
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let structured = result.structured_content.expect("diff structured content");
+    let added: Vec<&str> = structured["added"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(added, vec!["rust", "concise"]);
+    assert!(structured["removed"].as_array().unwrap().is_empty());
+    assert!(structured["unified"]
+        .as_str()
+        .unwrap()
+        .contains("+## Communication Style"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn compose_layout_overrides() {
+    let client = common::spawn_server(None).await;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "persona": "backend-engineer",
+                    "skills": ["go"],
+                    "constraints": ["no-frameworks"],
+                    "layout": {
+                        "order": ["constraints"],
+                        "headings": { "constraint": "Hard Rules", "skill": "" }
+                    }
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let text = result.content[0].as_text().unwrap().text.clone();
+    assert!(text.starts_with("## Hard Rules\n\n"), "{text}");
+    assert!(!text.contains("## Expertise"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn custom_category_is_composable_listable_and_completable() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("config.yaml"),
+        "categories:\n  - name: mcp-output-format\n    heading: Output Format\n",
+    )
+    .unwrap();
+    let dir = project.path().join("mcp-output-format");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("json.md"), "Respond with JSON only.").unwrap();
+    let client = common::spawn_server(Some(project.path().to_path_buf())).await;
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("compose"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({
+                    "persona": "backend-engineer",
+                    "custom": { "mcp-output-format": ["json"] }
+                }))
+                .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+    let text = result.content[0].as_text().unwrap().text.clone();
+    assert!(
+        text.ends_with("## Output Format\n\nRespond with JSON only."),
+        "{text}"
+    );
+
+    let templates = client.list_all_resource_templates().await.unwrap();
+    let fragment = templates.iter().find(|t| t.name == "fragment").unwrap();
+    let description = fragment.description.as_deref().unwrap();
+    assert!(
+        description.contains("examples, mcp-output-format"),
+        "{description}"
+    );
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: Cow::Borrowed("list"),
+            arguments: Some(
+                serde_json::from_value(serde_json::json!({ "category": "mcp-output-format" }))
+                    .unwrap(),
+            ),
+            task: None,
+        })
+        .await
+        .unwrap();
+    let structured = result.structured_content.unwrap();
+    let fragments = structured["fragments"].as_array().unwrap();
+    assert_eq!(fragments.len(), 1);
+    assert_eq!(fragments[0]["category"], "mcp-output-format");
+
+    let contents = client
+        .read_resource(ReadResourceRequestParams {
+            uri: "mimic://fragments/mcp-output-format/json".to_string(),
+            meta: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(contents.contents.len(), 1);
+
+    let result = client
+        .complete(CompleteRequestParams {
+            meta: None,
+            r#ref: Reference::for_prompt("mimic-backend-engineer"),
+            argument: ArgumentInfo {
+                name: "mcp-output-format".to_string(),
+                value: "js".to_string(),
+            },
+            context: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.completion.values, vec!["json"]);
+}